@@ -0,0 +1,195 @@
+use std::cell::Cell;
+use std::time::Instant;
+
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{Order, OwnedDeps, Pair, Storage, Uint128};
+use cw20::{Cw20Coin, MinterResponse};
+use token_contract::contract::{execute, instantiate, query};
+use token_contract::msg::{Execute, Instantiate, Query, UpdateType};
+
+/// Number of holders created before measuring, so the storage is not trivially small
+const HOLDERS: u32 = 2_000;
+const RUNS: u32 = 500;
+
+/// Storage counting the entries read and written, which is what gas is mostly spent on
+#[derive(Default)]
+struct CountingStorage {
+    inner: MockStorage,
+    reads: Cell<u64>,
+    writes: Cell<u64>,
+}
+
+impl CountingStorage {
+    fn reset(&self) -> (u64, u64) {
+        (self.reads.replace(0), self.writes.replace(0))
+    }
+}
+
+impl Storage for CountingStorage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.reads.set(self.reads.get() + 1);
+        self.inner.get(key)
+    }
+
+    fn range<'a>(
+        &'a self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Pair> + 'a> {
+        let reads = &self.reads;
+        Box::new(self.inner.range(start, end, order).inspect(move |_| {
+            reads.set(reads.get() + 1);
+        }))
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.set(self.writes.get() + 1);
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.set(self.writes.get() + 1);
+        self.inner.remove(key)
+    }
+}
+
+type Deps = OwnedDeps<CountingStorage, MockApi, MockQuerier>;
+
+struct Row {
+    operation: &'static str,
+    micros: f64,
+    reads: f64,
+    writes: f64,
+}
+
+fn holder(index: u32) -> String {
+    format!("holder{:05}", index)
+}
+
+fn setup() -> Deps {
+    let mut deps = OwnedDeps {
+        storage: CountingStorage::default(),
+        api: MockApi::default(),
+        querier: MockQuerier::new(&[]),
+    };
+    let msg = Instantiate {
+        name: "Bench Token".to_string(),
+        symbol: "BENCH".to_string(),
+        decimals: 6,
+        initial_balances: (0..HOLDERS)
+            .map(|index| Cw20Coin {
+                address: holder(index),
+                amount: Uint128::new(1_000_000),
+            })
+            .collect(),
+        frozen_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: "creator".to_string(),
+            cap: None,
+        }),
+        bal_cap: Uint128::new(u128::MAX),
+        vesting: vec![],
+        marketing: None,
+        initial_whitelist: vec![],
+        initial_burnlist: vec![],
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    for index in (0..HOLDERS).step_by(4) {
+        let msg = Execute::UpdateFrozenList(UpdateType::Add(Cw20Coin {
+            address: holder(index),
+            amount: Uint128::new(1_000),
+        }));
+        execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    }
+    deps
+}
+
+/// Runs `op` `RUNS` times and averages its wall time and storage accesses
+fn measure(operation: &'static str, deps: &mut Deps, mut op: impl FnMut(&mut Deps, u32)) -> Row {
+    deps.storage.reset();
+    let start = Instant::now();
+    for run in 0..RUNS {
+        op(deps, run);
+    }
+    let elapsed = start.elapsed();
+    let (reads, writes) = deps.storage.reset();
+
+    let runs = f64::from(RUNS);
+    Row {
+        operation,
+        micros: elapsed.as_secs_f64() * 1e6 / runs,
+        reads: reads as f64 / runs,
+        writes: writes as f64 / runs,
+    }
+}
+
+/// Prints a markdown table of the average cost of the hot paths, e.g. to compare
+/// storage layouts before and after a change: `cargo bench-report > bench_output.txt`
+fn main() {
+    let mut deps = setup();
+    let page = |start_after: u32| Some(holder(start_after % (HOLDERS - 30)));
+
+    let rows = vec![
+        measure("transfer", &mut deps, |deps, run| {
+            let msg = Execute::Transfer {
+                recipient: holder((run + 1) % HOLDERS),
+                amount: Uint128::new(10),
+            };
+            let info = mock_info(&holder(run % HOLDERS), &[]);
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }),
+        measure("mint", &mut deps, |deps, run| {
+            let msg = Execute::Mint {
+                recipient: holder(run % HOLDERS),
+                amount: Uint128::new(10),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        }),
+        measure("burn", &mut deps, |deps, run| {
+            let msg = Execute::Burn {
+                amount: Uint128::new(10),
+            };
+            let info = mock_info(&holder(run % HOLDERS), &[]);
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }),
+        measure("balance", &mut deps, |deps, run| {
+            let msg = Query::Balance {
+                address: holder(run % HOLDERS),
+            };
+            query(deps.as_ref(), mock_env(), msg).unwrap();
+        }),
+        measure("all_accounts (30)", &mut deps, |deps, run| {
+            let msg = Query::AllAccounts {
+                start_after: page(run),
+                limit: Some(30),
+            };
+            query(deps.as_ref(), mock_env(), msg).unwrap();
+        }),
+        measure("all_frozen_balances (30)", &mut deps, |deps, run| {
+            let msg = Query::AllFrozenBalances {
+                start_after: page(run),
+                limit: Some(30),
+            };
+            query(deps.as_ref(), mock_env(), msg).unwrap();
+        }),
+        measure("top_holders (30)", &mut deps, |deps, run| {
+            let msg = Query::TopHolders {
+                start_after: page(run),
+                limit: Some(30),
+            };
+            query(deps.as_ref(), mock_env(), msg).unwrap();
+        }),
+    ];
+
+    println!("holders: {}, runs per operation: {}", HOLDERS, RUNS);
+    println!();
+    println!("| operation | time (µs) | storage reads | storage writes |");
+    println!("|---|---:|---:|---:|");
+    for row in rows {
+        println!(
+            "| {} | {:.1} | {:.1} | {:.1} |",
+            row.operation, row.micros, row.reads, row.writes
+        );
+    }
+}
@@ -0,0 +1,101 @@
+use std::env::current_dir;
+use std::fs::create_dir_all;
+
+use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+
+use cw20::{
+    AllAccountsResponse, AllAllowancesResponse, AllowanceResponse, BalanceResponse,
+    DownloadLogoResponse, MarketingInfoResponse, MinterResponse, TokenInfoResponse,
+};
+use token_contract::msg::{
+    ClaimsResponse, Execute, Instantiate, MigrateMsg, PolicyQueryMsg, PolicyResponse, Query,
+    SudoMsg, TokenHookMsg, TransferHookMsg,
+};
+#[cfg(feature = "migration-preview")]
+use token_contract::responses::MigrationPreviewResponse;
+use token_contract::responses::{
+    AccountStatsResponse, AirdropStageResponse, AllFrozenBalancesResponse, AttestationResponse,
+    AuthorizationStateResponse, BalanceAtResponse, BurnStatsResponse, CapabilitiesResponse,
+    CirculatingSupplyResponse, DelegationResponse, FrozenBalanceResponse, FrozenScheduleResponse,
+    IsBlacklistedResponse, IsClaimedResponse, IsFrozenResponse, IsWhitelistedResponse,
+    MintersResponse, PendingActionsResponse, PendingRewardsResponse, PermitNonceResponse,
+    PolicyContractResponse, RiskSnapshotResponse, RolesResponse, RouterOptInResponse,
+    RouterResponse, SelfLockResponse, SpendableBalanceResponse, StakedResponse,
+    SupplyBreakdownResponse, TokenHooksResponse, TopHoldersResponse, TotalSupplyAtResponse,
+    TransferFeeResponse, TransferMemosResponse, TransferPolicyResponse, VestingInfoResponse,
+    VotingPowerResponse,
+};
+
+/// Writes the JSON schema of every message and response into `schema/`.
+/// The directory is cleared first so the output only depends on the types.
+fn main() {
+    let mut out_dir = current_dir().unwrap();
+    out_dir.push("schema");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    // entry point messages
+    export_schema(&schema_for!(Instantiate), &out_dir);
+    export_schema(&schema_for!(Execute), &out_dir);
+    export_schema(&schema_for!(Query), &out_dir);
+    export_schema(&schema_for!(SudoMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
+
+    // messages sent to and queried from other contracts
+    export_schema(&schema_for!(TransferHookMsg), &out_dir);
+    export_schema(&schema_for!(TokenHookMsg), &out_dir);
+    export_schema(&schema_for!(PolicyQueryMsg), &out_dir);
+    export_schema(&schema_for!(PolicyResponse), &out_dir);
+
+    // cw20 query responses
+    export_schema(&schema_for!(BalanceResponse), &out_dir);
+    export_schema(&schema_for!(TokenInfoResponse), &out_dir);
+    export_schema(&schema_for!(AllowanceResponse), &out_dir);
+    export_schema(&schema_for!(MinterResponse), &out_dir);
+    export_schema(&schema_for!(AllAccountsResponse), &out_dir);
+    export_schema(&schema_for!(AllAllowancesResponse), &out_dir);
+    export_schema(&schema_for!(MarketingInfoResponse), &out_dir);
+    export_schema(&schema_for!(DownloadLogoResponse), &out_dir);
+
+    // token contract query responses
+    export_schema(&schema_for!(AccountStatsResponse), &out_dir);
+    export_schema(&schema_for!(AirdropStageResponse), &out_dir);
+    export_schema(&schema_for!(AllFrozenBalancesResponse), &out_dir);
+    export_schema(&schema_for!(AttestationResponse), &out_dir);
+    export_schema(&schema_for!(AuthorizationStateResponse), &out_dir);
+    export_schema(&schema_for!(BalanceAtResponse), &out_dir);
+    export_schema(&schema_for!(BurnStatsResponse), &out_dir);
+    export_schema(&schema_for!(CapabilitiesResponse), &out_dir);
+    export_schema(&schema_for!(CirculatingSupplyResponse), &out_dir);
+    export_schema(&schema_for!(ClaimsResponse), &out_dir);
+    export_schema(&schema_for!(DelegationResponse), &out_dir);
+    export_schema(&schema_for!(FrozenBalanceResponse), &out_dir);
+    export_schema(&schema_for!(FrozenScheduleResponse), &out_dir);
+    export_schema(&schema_for!(IsBlacklistedResponse), &out_dir);
+    export_schema(&schema_for!(IsClaimedResponse), &out_dir);
+    export_schema(&schema_for!(IsFrozenResponse), &out_dir);
+    export_schema(&schema_for!(IsWhitelistedResponse), &out_dir);
+    export_schema(&schema_for!(MintersResponse), &out_dir);
+    export_schema(&schema_for!(PendingActionsResponse), &out_dir);
+    export_schema(&schema_for!(PendingRewardsResponse), &out_dir);
+    export_schema(&schema_for!(PermitNonceResponse), &out_dir);
+    export_schema(&schema_for!(PolicyContractResponse), &out_dir);
+    export_schema(&schema_for!(RiskSnapshotResponse), &out_dir);
+    export_schema(&schema_for!(RolesResponse), &out_dir);
+    export_schema(&schema_for!(RouterOptInResponse), &out_dir);
+    export_schema(&schema_for!(RouterResponse), &out_dir);
+    export_schema(&schema_for!(SelfLockResponse), &out_dir);
+    export_schema(&schema_for!(SpendableBalanceResponse), &out_dir);
+    export_schema(&schema_for!(StakedResponse), &out_dir);
+    export_schema(&schema_for!(SupplyBreakdownResponse), &out_dir);
+    export_schema(&schema_for!(TokenHooksResponse), &out_dir);
+    export_schema(&schema_for!(TopHoldersResponse), &out_dir);
+    export_schema(&schema_for!(TotalSupplyAtResponse), &out_dir);
+    export_schema(&schema_for!(TransferFeeResponse), &out_dir);
+    export_schema(&schema_for!(TransferMemosResponse), &out_dir);
+    export_schema(&schema_for!(TransferPolicyResponse), &out_dir);
+    export_schema(&schema_for!(VestingInfoResponse), &out_dir);
+    export_schema(&schema_for!(VotingPowerResponse), &out_dir);
+    #[cfg(feature = "migration-preview")]
+    export_schema(&schema_for!(MigrationPreviewResponse), &out_dir);
+}
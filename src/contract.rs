@@ -1,37 +1,120 @@
 use super::*;
 use crate::{
     error::ContractError,
-    msg::{Execute, Instantiate, Query, UpdateType},
+    logic::{
+        self, active_tranches, boosted_limit, ensure_mint_cap, unvested_amount, validate_schedule,
+        verify_logo, TransferCharges,
+    },
+    merkle::verify_proof,
+    msg::{
+        AccountStatsResponse, AirdropStageResponse, AllFrozenBalancesResponse, AttestationEntry,
+        AttestationResponse, AuthorizationStateResponse, BalanceAtResponse, BurnStatsResponse,
+        CapabilitiesResponse, CirculatingSupplyResponse, ClaimsResponse, DelegationResponse,
+        Execute, FrozenBalanceResponse, FrozenScheduleResponse, Instantiate, IsBlacklistedResponse,
+        IsClaimedResponse, IsFrozenResponse, IsWhitelistedResponse, MigrateMsg, MinterInfo,
+        MintersResponse, PendingActionsResponse, PendingRewardsResponse, Permit,
+        PermitNonceResponse, PermitPayload, PolicyContractResponse, PolicyQueryMsg, PolicyResponse,
+        Query, RiskSnapshotResponse, RolesResponse, RouterOptInResponse, RouterResponse,
+        SelfLockResponse, SpendableBalanceResponse, StakedResponse, SudoMsg,
+        SupplyBreakdownResponse, TokenHookMsg, TokenHooksResponse, TopHoldersResponse,
+        TotalSupplyAtResponse, TransferAuthorization, TransferFeeResponse, TransferHookMsg,
+        TransferMemosResponse, TransferPolicyResponse, UpdateType, VestingInfoResponse,
+        VotingPowerResponse,
+    },
+    operations::{
+        checkpoint, delegate_of, earning_supply, ensure_cap, ensure_memo_not_required, ensure_mint,
+        ensure_not_blacklisted, ensure_spendable, ensure_transfer, frozen_amount, index_holder,
+        pending_rewards, reward_weight, settle_rewards, snapshot_balances, transfer_charges,
+        update_frozen_total,
+    },
+    signature::verify_signature,
 };
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    entry_point, to_binary, Addr, BankMsg, Binary, Coin, ContractResult, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, Event, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsg,
     Uint128,
 };
-use cw2::set_contract_version;
-use cw20::BalanceResponse;
+use cw0::Expiration;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{AllowanceResponse, Cw20Coin, Logo, LogoInfo, MarketingInfoResponse};
 use cw20_base::{
     allowances::{
-        execute_burn_from, execute_decrease_allowance, execute_increase_allowance,
-        execute_send_from, execute_transfer_from, query_allowance,
+        deduct_allowance, execute_burn_from, execute_decrease_allowance,
+        execute_increase_allowance, execute_send_from, execute_transfer_from, query_allowance,
     },
     contract::{
         execute_burn, execute_mint, execute_send, execute_transfer, execute_update_marketing,
-        execute_upload_logo, query_balance, query_minter, query_token_info,
+        execute_upload_logo, query_balance, query_download_logo, query_marketing_info,
+        query_minter, query_token_info,
     },
+    enumerable::{query_all_accounts, query_all_allowances},
     state::*,
 };
+use cw_storage_plus::{Bound, PrimaryKey, U128Key, U64Key, U8Key};
+
+#[cfg(feature = "migration-preview")]
+use crate::msg::{MigrationPreviewResponse, NamespacePreview};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "token_contract";
-const CONTRACT_VERSION: &str = "1.0.0";
+const CONTRACT_VERSION: &str = "1.6.0";
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+// accounts gone through per backfill step
+const MAX_BACKFILL_LIMIT: u32 = 500;
+const DEFAULT_BACKFILL_LIMIT: u32 = 100;
+/// Reply id of token hook notifications, well above the ids given to sends
+const TOKEN_HOOK_REPLY_ID: u64 = u64::MAX;
+
+// extensions always supported, "mintable" is added when a minter is configured
+const CAPABILITIES: &[&str] = &[
+    "allowances",
+    "enumerable",
+    "marketing",
+    "freezable",
+    "balance-cap",
+    "rate-limit",
+    "burn-addresses",
+    "router",
+    "account-freeze",
+    "frozen-schedule",
+    "vesting",
+    "clawback",
+    "blacklist",
+    "transfer-hooks",
+    "roles",
+    "timelock",
+    "self-lock",
+    "sudo",
+    "permit",
+    "transfer-authorization",
+    "snapshots",
+    "delegation",
+    "rewards",
+    "staking",
+    "airdrop",
+    "transfer-fee",
+    "minters",
+    "burn-tax",
+    "transfer-policy",
+    "kyc",
+    "travel-rule",
+    "account-stats",
+    "supply-breakdown",
+    "token-hooks",
+    "policy-contract",
+    "top-holders",
+];
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     mut deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: Instantiate,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
@@ -48,13 +131,18 @@ pub fn instantiate(
         return Err(ContractError::CannotExceedCap {});
     }
 
+    for account in &msg.vesting {
+        validate_schedule(&account.schedule)?;
+    }
+
     // create initial accounts
     let total_supply = create_accounts(&mut deps, &msg)?;
 
     if let Some(limit) = msg.get_cap() {
         if total_supply > limit {
-            return Err(StdError::generic_err("Initial supply greater than cap").into());
+            return Err(ContractError::InitialSupplyExceedsCap {});
         }
+        SUPPLY_CAP.save(deps.storage, &limit)?;
     }
 
     let mint = match msg.mint {
@@ -75,6 +163,42 @@ pub fn instantiate(
     };
     TOKEN_INFO.save(deps.storage, &data)?;
 
+    if let Some(marketing) = msg.marketing {
+        let logo = match marketing.logo {
+            Some(logo) => {
+                verify_logo(&logo)?;
+                LOGO.save(deps.storage, &logo)?;
+                Some(match logo {
+                    Logo::Url(url) => LogoInfo::Url(url),
+                    Logo::Embedded(_) => LogoInfo::Embedded,
+                })
+            }
+            None => None,
+        };
+        let marketing_info = MarketingInfoResponse {
+            project: marketing.project,
+            description: marketing.description,
+            marketing: marketing
+                .marketing
+                .map(|addr| deps.api.addr_validate(&addr))
+                .transpose()?,
+            logo,
+        };
+        MARKETING_INFO.save(deps.storage, &marketing_info)?;
+    }
+
+    let accounts = msg
+        .initial_balances
+        .iter()
+        .map(|account| deps.api.addr_validate(&account.address))
+        .collect::<StdResult<Vec<_>>>()?;
+    let accounts: Vec<_> = accounts.iter().collect();
+    checkpoint(deps.storage, &env, &accounts)?;
+    // the instantiator starts with every role and can hand them out afterwards
+    for role in Role::ALL {
+        ROLES.save(deps.storage, (&info.sender, role.key()), &true)?;
+    }
+
     Ok(Response::new().add_attribute("action", "intantiated"))
 }
 
@@ -88,7 +212,26 @@ fn create_accounts(deps: &mut DepsMut, msg: &Instantiate) -> StdResult<Uint128>
 
     for account in &msg.frozen_balances {
         let address = deps.api.addr_validate(&account.address)?;
+        let previous = FROZEN_BALANCES
+            .may_load(deps.storage, &address)?
+            .unwrap_or_default();
         FROZEN_BALANCES.save(deps.storage, &address, &account.amount)?;
+        update_frozen_total(deps.storage, previous, account.amount)?;
+    }
+
+    for account in &msg.vesting {
+        let address = deps.api.addr_validate(&account.address)?;
+        VESTING.save(deps.storage, &address, &account.schedule)?;
+    }
+
+    for address in &msg.initial_whitelist {
+        let address = deps.api.addr_validate(address)?;
+        WHITELIST.save(deps.storage, &address, &true)?;
+    }
+
+    for address in &msg.initial_burnlist {
+        let address = deps.api.addr_validate(address)?;
+        BURN_ADDRESSES.save(deps.storage, &address, &true)?;
     }
 
     BALANCE_CAP.save(deps.storage, &msg.bal_cap)?;
@@ -105,12 +248,49 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         Execute::Mint { recipient, amount } => mint(deps, env, info, recipient, amount),
+        Execute::MintBatch { recipients } => mint_batch(deps, env, info, recipients),
+        Execute::UpdateMinter { new_minter } => update_minter(deps, info, new_minter),
+        Execute::AddMinter { address, cap } => add_minter(deps, info, address, cap),
+        Execute::RemoveMinter { address } => remove_minter(deps, info, address),
+        Execute::SetMinterCap { address, cap } => set_minter_cap(deps, info, address, cap),
         Execute::Transfer { recipient, amount } => transfer(deps, env, info, recipient, amount),
+        Execute::TransferWithMemo {
+            recipient,
+            amount,
+            memo_hash,
+        } => transfer_with_memo(deps, env, info, recipient, amount, memo_hash),
+        Execute::TransferBatch { transfers } => transfer_batch(deps, env, info, transfers),
         Execute::Send {
             contract,
             amount,
             msg,
         } => send(deps, env, info, contract, amount, msg),
+        Execute::TransferWithAuthorization {
+            from,
+            to,
+            amount,
+            valid_after,
+            valid_before,
+            nonce,
+            pubkey,
+            signature,
+        } => transfer_with_authorization(
+            deps,
+            env.clone(),
+            // the signed payload is bound to this chain and contract
+            TransferAuthorization {
+                chain_id: env.block.chain_id,
+                contract: env.contract.address.into(),
+                from,
+                to,
+                amount,
+                valid_after,
+                valid_before,
+                nonce,
+            },
+            pubkey,
+            signature,
+        ),
         Execute::Burn { amount } => burn(deps, env, info, amount),
         Execute::IncreaseAllowance {
             spender,
@@ -126,6 +306,17 @@ pub fn execute(
         } => Ok(execute_decrease_allowance(
             deps, env, info, spender, amount, expires,
         )?),
+        Execute::PermitAllowance {
+            owner,
+            spender,
+            amount,
+            nonce,
+            expires,
+            pubkey,
+            signature,
+        } => permit_allowance(
+            deps, env, owner, spender, amount, nonce, expires, pubkey, signature,
+        ),
         Execute::TransferFrom {
             owner,
             recipient,
@@ -138,6 +329,24 @@ pub fn execute(
             amount,
             msg,
         } => send_from(deps, env, info, owner, contract, amount, msg),
+        Execute::PermitAndCall {
+            permit,
+            call,
+            contract,
+        } => permit_and_call(deps, env, info, permit, call, contract),
+        Execute::AuthorizeRouter {
+            router,
+            max_per_tx,
+            expiry,
+        } => authorize_router(deps, info, router, max_per_tx, expiry),
+        Execute::RevokeRouter { router } => revoke_router(deps, info, router),
+        Execute::OptInRouter { router, amount } => opt_in_router(deps, info, router, amount),
+        Execute::OptOutRouter { router } => opt_in_router(deps, info, router, Uint128::zero()),
+        Execute::RouterTransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => router_transfer_from(deps, env, info, owner, recipient, amount),
         Execute::UpdateMarketing {
             project,
             description,
@@ -151,12 +360,98 @@ pub fn execute(
             marketing,
         )?),
         Execute::UploadLogo(logo) => Ok(execute_upload_logo(deps, env, info, logo)?),
-        Execute::UpdateFrozenList(update_type) => Ok(update_frozen_list(update_type, deps)?),
+        Execute::UpdateFrozenList(update_type) => update_frozen_list(deps, env, info, update_type),
+        Execute::UpdateBalanceCap { new_cap } => update_balance_cap(deps, info, new_cap),
+        Execute::SetTimelockDelay { delay } => set_timelock_delay(deps, info, delay),
+        Execute::Schedule { action } => schedule(deps, env, info, action),
+        Execute::ExecuteScheduled { id } => execute_scheduled(deps, env, info, id),
+        Execute::CancelScheduled { id } => cancel_scheduled(deps, info, id),
+        Execute::SetRateLimit { limit } => set_rate_limit(deps, info, limit),
+        Execute::SetRateLimitExemption { address, exempt } => {
+            set_rate_limit_exemption(deps, info, address, exempt)
+        }
+        Execute::SetBurnAddress { address, burn } => {
+            set_burn_address(deps, env, info, address, burn)
+        }
+        Execute::FreezeAccount { address } => update_account_freeze(deps, info, address, true),
+        Execute::UnfreezeAccount { address } => update_account_freeze(deps, info, address, false),
+        Execute::GrantRole { address, role } => update_role(deps, info, address, role, true),
+        Execute::RevokeRole { address, role } => update_role(deps, info, address, role, false),
+        Execute::LockSelf { until } => lock_self(deps, env, info, until),
+        Execute::UnlockSelf { owner } => unlock_self(deps, info, owner),
+        Execute::SetTransferFee { bps, collector } => set_transfer_fee(deps, info, bps, collector),
+        Execute::SetFeeExemption { address, exempt } => {
+            set_fee_exemption(deps, info, address, exempt)
+        }
+        Execute::SetBurnRate { bps } => set_burn_rate(deps, info, bps),
+        Execute::RegisterMerkleRoot {
+            merkle_root,
+            total_amount,
+        } => register_merkle_root(deps, info, merkle_root, total_amount),
+        Execute::ClaimAirdrop {
+            stage,
+            amount,
+            proof,
+        } => claim_airdrop(deps, env, info, stage, amount, proof),
+        Execute::Stake { amount } => stake(deps, env, info, amount),
+        Execute::Unstake { amount } => unstake(deps, env, info, amount),
+        Execute::ClaimUnstaked {} => claim_unstaked(deps, env, info),
+        Execute::UpdateStakingConfig {
+            unbonding_period,
+            rate_limit_boost,
+        } => update_staking_config(deps, info, unbonding_period, rate_limit_boost),
+        Execute::SetRewardDenom { denom } => set_reward_denom(deps, info, denom),
+        Execute::DistributeRewards { amount } => distribute_rewards(deps, env, info, amount),
+        Execute::ClaimRewards {} => claim_rewards(deps, info),
+        Execute::Delegate { delegatee } => delegate(deps, env, info, delegatee),
+        Execute::SetRecoveryAddress { address } => set_recovery_address(deps, env, info, address),
+        Execute::RegisterTransferHook {} => register_transfer_hook(deps, info, true),
+        Execute::UnregisterTransferHook {} => register_transfer_hook(deps, info, false),
+        Execute::SetTransferPolicy { policy } => set_transfer_policy(deps, info, policy),
+        Execute::SetWhitelisted {
+            address,
+            whitelisted,
+        } => set_whitelisted(deps, info, address, whitelisted),
+        Execute::Attest {
+            address,
+            level,
+            expires,
+        } => attest(
+            deps,
+            info,
+            vec![AttestationEntry {
+                address,
+                level,
+                expires,
+            }],
+        ),
+        Execute::AttestBatch { attestations } => attest(deps, info, attestations),
+        Execute::RevokeAttestation { address } => revoke_attestation(deps, info, address),
+        Execute::SetKycThreshold { threshold } => set_kyc_threshold(deps, info, threshold),
+        Execute::SetTravelRuleThreshold { threshold } => {
+            set_travel_rule_threshold(deps, info, threshold)
+        }
+        Execute::AddTokenHook { address } => add_token_hook(deps, info, address),
+        Execute::SetPolicyContract { address, fail_open } => {
+            set_policy_contract(deps, info, address, fail_open)
+        }
+        Execute::RemoveTokenHook { address } => remove_token_hook(deps, info, address),
+        Execute::BlackList { address } => update_blacklist(deps, info, address, true),
+        Execute::RemoveBlackList { address } => update_blacklist(deps, info, address, false),
+        Execute::Clawback {
+            owner,
+            recipient,
+            amount,
+        } => clawback(deps, env, info, owner, recipient, amount),
+        Execute::CreateVesting { address, schedule } => {
+            create_vesting(deps, info, address, schedule)
+        }
+        Execute::MigrateHolders { limit } => migrate_holders(deps, env, limit),
     }
 }
 
 pub fn mint(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     recipient: String,
@@ -164,13 +459,194 @@ pub fn mint(
 ) -> Result<Response, ContractError> {
     // ensuring balance capital is not exceeded for an user
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
-    let token_bal = BALANCES.load(deps.storage, &rcpt_addr)?;
-    let bal_cap = BALANCE_CAP.load(deps.storage)?;
-    if (token_bal + amount) > bal_cap {
-        return Err(ContractError::CannotExceedCap {});
+    ensure_mint(deps.storage, &rcpt_addr, amount)?;
+    let action = TokenHookMsg::Mint {
+        recipient: rcpt_addr.to_string(),
+        amount,
+    };
+    ensure_policy_allows(deps.as_ref(), action)?;
+
+    // registered minters mint within their own cap, anyone else goes through cw20-base
+    if let Some(mut allowance) = MINTERS.may_load(deps.storage, &info.sender)? {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        allowance.minted = match allowance.minted.checked_add(amount) {
+            Ok(minted) if allowance.cap.is_none_or(|cap| minted <= cap) => minted,
+            _ => return Err(ContractError::MinterCapExceeded {}),
+        };
+        MINTERS.save(deps.storage, &info.sender, &allowance)?;
+        mint_tokens(deps.storage, &rcpt_addr, amount)?;
+        checkpoint(deps.storage, &env, &[&rcpt_addr])?;
+
+        let hooks = token_hooks(
+            deps.storage,
+            TokenHookMsg::Mint {
+                recipient: rcpt_addr.to_string(),
+                amount,
+            },
+        )?;
+        let res = Response::new()
+            .add_attribute("action", "mint")
+            .add_attribute("to", rcpt_addr)
+            .add_attribute("amount", amount)
+            .add_attribute("by", info.sender)
+            .add_submessages(hooks);
+        return Ok(res);
+    }
+
+    let res = execute_mint(deps.branch(), env.clone(), info, recipient, amount)?;
+    checkpoint(deps.storage, &env, &[&rcpt_addr])?;
+    let hooks = token_hooks(
+        deps.storage,
+        TokenHookMsg::Mint {
+            recipient: rcpt_addr.into(),
+            amount,
+        },
+    )?;
+    Ok(res.add_submessages(hooks))
+}
+
+/// Creates `amount` new tokens for `recipient`, bounded by the supply cap
+fn mint_tokens(
+    storage: &mut dyn Storage,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let mut token_info = TOKEN_INFO.load(storage)?;
+    token_info.total_supply = ensure_mint_cap(
+        token_info.total_supply,
+        amount,
+        SUPPLY_CAP.may_load(storage)?,
+    )?;
+    TOKEN_INFO.save(storage, &token_info)?;
+    BALANCES.update(
+        storage,
+        recipient,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+    )?;
+    Ok(())
+}
+
+fn add_minter(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    cap: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    let allowance = MinterAllowance {
+        cap,
+        minted: Uint128::zero(),
+    };
+    MINTERS.save(deps.storage, &address, &allowance)?;
+
+    let res = Response::new()
+        .add_attribute("action", "add_minter")
+        .add_attribute("minter", address)
+        .add_attribute("cap", cap.map(|cap| cap.to_string()).unwrap_or_default());
+    Ok(res)
+}
+
+fn remove_minter(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    MINTERS.remove(deps.storage, &address);
+
+    let res = Response::new()
+        .add_attribute("action", "remove_minter")
+        .add_attribute("minter", address);
+    Ok(res)
+}
+
+fn set_minter_cap(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    cap: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    // what was minted so far keeps counting against the new cap
+    let address = deps.api.addr_validate(&address)?;
+    let mut allowance = MINTERS.load(deps.storage, &address)?;
+    allowance.cap = cap;
+    MINTERS.save(deps.storage, &address, &allowance)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_minter_cap")
+        .add_attribute("minter", address)
+        .add_attribute("cap", cap.map(|cap| cap.to_string()).unwrap_or_default());
+    Ok(res)
+}
+
+fn mint_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipients: Vec<Cw20Coin>,
+) -> Result<Response, ContractError> {
+    if recipients.is_empty() {
+        return Err(ContractError::InvalidZeroAmount {});
     }
 
-    Ok(execute_mint(deps, env, info, recipient, amount)?)
+    let mut res = Response::new().add_attribute("action", "mint_batch");
+    for coin in recipients {
+        let mint_res = mint(
+            deps.branch(),
+            env.clone(),
+            info.clone(),
+            coin.address,
+            coin.amount,
+        )?;
+        res = res
+            .add_attributes(mint_res.attributes)
+            .add_submessages(mint_res.messages);
+    }
+
+    Ok(res)
+}
+
+fn update_minter(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_minter: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut token_info = TOKEN_INFO.load(deps.storage)?;
+    let previous = token_info.mint.as_ref().map(|m| m.minter.clone());
+    if previous.as_ref() != Some(&info.sender) {
+        ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+    }
+
+    let new_minter = new_minter
+        .map(|minter| deps.api.addr_validate(&minter))
+        .transpose()?;
+    // revoking only drops the authority, the supply cap stays in `SUPPLY_CAP`
+    let cap = SUPPLY_CAP.may_load(deps.storage)?;
+    token_info.mint = new_minter.clone().map(|minter| MinterData { minter, cap });
+    TOKEN_INFO.save(deps.storage, &token_info)?;
+
+    let event = Event::new("update_minter")
+        .add_attribute(
+            "previous_minter",
+            previous.map(String::from).unwrap_or_default(),
+        )
+        .add_attribute(
+            "new_minter",
+            new_minter.map(String::from).unwrap_or_default(),
+        )
+        .add_attribute("by", info.sender);
+    let res = Response::new()
+        .add_attribute("action", "update_minter")
+        .add_event(event);
+    Ok(res)
 }
 
 fn transfer(
@@ -180,119 +656,307 @@ fn transfer(
     recipient: String,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
-    // Ensuring balance is unlocked for transaction
-    let balance = BALANCES.load(deps.storage, &info.sender)?;
-    let frozen_balance = FROZEN_BALANCES
-        .load(deps.storage, &info.sender)
-        .unwrap_or(Uint128::zero());
-    if (balance - frozen_balance) < amount {
-        return Err(ContractError::BalanceFrozen {});
-    }
+    ensure_memo_not_required(deps.storage, amount)?;
+    move_transfer(deps, env, info, recipient, amount)
+}
 
-    // ensuring balance capital is not exceeded for an user
+fn transfer_with_memo(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+    memo_hash: String,
+) -> Result<Response, ContractError> {
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(&memo_hash, &mut hash).map_err(|_| ContractError::InvalidMemoHash {})?;
+
+    let id = NEXT_MEMO_ID.may_load(deps.storage)?.unwrap_or_default();
+    NEXT_MEMO_ID.save(deps.storage, &(id + 1))?;
+    let memo = TransferMemo {
+        id,
+        from: info.sender.clone(),
+        to: deps.api.addr_validate(&recipient)?,
+        amount,
+        memo_hash,
+        height: env.block.height,
+    };
+    TRANSFER_MEMOS.save(deps.storage, U64Key::from(id), &memo)?;
+
+    let res = move_transfer(deps, env, info, recipient, amount)?;
+    Ok(res
+        .add_attribute("memo_id", id.to_string())
+        .add_attribute("memo_hash", memo.memo_hash))
+}
+
+/// Moves `amount` from the sender to `recipient` with every transfer check applied but
+/// the travel rule, which the callers settle
+fn move_transfer(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
-    let token_bal = BALANCES
-        .load(deps.storage, &rcpt_addr)
-        .unwrap_or(Uint128::default());
-    let bal_cap = BALANCE_CAP.load(deps.storage)?;
-    if (token_bal + amount) > bal_cap {
-        return Err(ContractError::CannotExceedCap {});
+    let sender = info.sender.clone();
+    let charges = transfer_charges(deps.storage, &sender, &rcpt_addr, amount)?;
+    let net = amount - charges.total();
+
+    // ensuring the balance is unlocked, the transfer policy and KYC requirements let both
+    // parties move tokens and balance capital is not exceeded for them or the fee collector
+    ensure_transfer(deps.storage, &env, &sender, &rcpt_addr, amount, charges)?;
+
+    consume_rate_limit(deps.storage, &env, &sender, amount)?;
+
+    let action = TokenHookMsg::Transfer {
+        sender: sender.to_string(),
+        recipient: rcpt_addr.to_string(),
+        amount,
+    };
+    ensure_policy_allows(deps.as_ref(), action)?;
+
+    charge_transfer_fee(deps.storage, &env, &sender, charges)?;
+
+    let hook = transfer_hook(deps.storage, &rcpt_addr, &sender, net)?;
+    let res = execute_transfer(deps.branch(), env.clone(), info, recipient, net)?;
+    checkpoint(deps.storage, &env, &[&sender, &rcpt_addr])?;
+    let hooks = token_hooks(
+        deps.storage,
+        TokenHookMsg::Transfer {
+            sender: sender.into(),
+            recipient: rcpt_addr.into(),
+            amount: net,
+        },
+    )?;
+    Ok(with_charges(res, charges)
+        .add_messages(hook)
+        .add_submessages(hooks))
+}
+
+fn transfer_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    transfers: Vec<Cw20Coin>,
+) -> Result<Response, ContractError> {
+    if transfers.is_empty() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // every transfer sees the balances left by the previous one
+    let mut res = Response::new().add_attribute("action", "transfer_batch");
+    for coin in transfers {
+        let transfer_res = transfer(
+            deps.branch(),
+            env.clone(),
+            info.clone(),
+            coin.address,
+            coin.amount,
+        )?;
+        res = res
+            .add_attributes(transfer_res.attributes)
+            .add_submessages(transfer_res.messages);
     }
 
-    Ok(execute_transfer(deps, env, info, recipient, amount)?)
+    Ok(res)
 }
 
 fn send(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     contract: String,
     amount: Uint128,
     msg: Binary,
 ) -> Result<Response, ContractError> {
-    // Ensuring balance is unlocked for transaction
-    let balance = BALANCES.load(deps.storage, &info.sender)?;
-    let frozen_balance = FROZEN_BALANCES
-        .load(deps.storage, &info.sender)
-        .unwrap_or(Uint128::zero());
-    if (balance - frozen_balance) < amount {
-        return Err(ContractError::BalanceFrozen {});
+    ensure_memo_not_required(deps.storage, amount)?;
+    let contract_addr = deps.api.addr_validate(&contract)?;
+    let owner = info.sender.clone();
+    let charges = transfer_charges(deps.storage, &owner, &contract_addr, amount)?;
+    let net = amount - charges.total();
+
+    // ensuring the balance is unlocked, the transfer policy and KYC requirements let both
+    // parties move tokens and balance capital is not exceeded for them or the fee collector
+    ensure_transfer(deps.storage, &env, &owner, &contract_addr, amount, charges)?;
+
+    let rate_limited = consume_rate_limit(deps.storage, &env, &owner, amount)?;
+
+    let action = TokenHookMsg::Transfer {
+        sender: owner.to_string(),
+        recipient: contract_addr.to_string(),
+        amount,
+    };
+    ensure_policy_allows(deps.as_ref(), action)?;
+
+    charge_transfer_fee(deps.storage, &env, &owner, charges)?;
+
+    let res = execute_send(deps.branch(), env.clone(), info, contract, net, msg)?;
+    checkpoint(deps.storage, &env, &[&owner, &contract_addr])?;
+    let pending = PendingSend {
+        owner,
+        contract: contract_addr,
+        amount: net,
+        fee: None,
+        burned: charges.burned,
+        rate_limited,
+    };
+    track_send(
+        deps.storage,
+        with_charges(res, charges),
+        pending,
+        charges.fee,
+    )
+}
+
+fn transfer_with_authorization(
+    deps: DepsMut,
+    env: Env,
+    authorization: TransferAuthorization,
+    pubkey: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    let now = env.block.time.seconds();
+    if now <= authorization.valid_after {
+        return Err(ContractError::AuthorizationNotYetValid {
+            valid_after: authorization.valid_after,
+        });
+    }
+    if now >= authorization.valid_before {
+        return Err(ContractError::Expired {});
+    }
+
+    let from = deps.api.addr_validate(&authorization.from)?;
+    let key = (&from, authorization.nonce.as_slice());
+    if USED_AUTHORIZATIONS.has(deps.storage, key) {
+        return Err(ContractError::AuthorizationUsed {});
     }
 
-    Ok(execute_send(deps, env, info, contract, amount, msg)?)
+    verify_signature(deps.api, &from, &pubkey, &authorization, &signature)?;
+    USED_AUTHORIZATIONS.save(deps.storage, key, &true)?;
+
+    let info = MessageInfo {
+        sender: from,
+        funds: vec![],
+    };
+    let res = transfer(deps, env, info, authorization.to, authorization.amount)?;
+    Ok(res.add_attribute("authorization_nonce", authorization.nonce.to_base64()))
 }
 
 fn burn(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     // Ensuring balance is unlocked for transaction
-    let balance = BALANCES.load(deps.storage, &info.sender)?;
-    let frozen_balance = FROZEN_BALANCES
-        .load(deps.storage, &info.sender)
-        .unwrap_or(Uint128::zero());
-    if (balance - frozen_balance) < amount {
-        return Err(ContractError::BalanceFrozen {});
-    }
+    ensure_spendable(deps.storage, &env, &info.sender, amount)?;
+    let action = TokenHookMsg::Burn {
+        owner: info.sender.to_string(),
+        amount,
+    };
+    ensure_policy_allows(deps.as_ref(), action)?;
 
-    Ok(execute_burn(deps, env, info, amount)?)
+    let sender = info.sender.clone();
+    let res = execute_burn(deps.branch(), env.clone(), info, amount)?;
+    checkpoint(deps.storage, &env, &[&sender])?;
+    let hooks = token_hooks(
+        deps.storage,
+        TokenHookMsg::Burn {
+            owner: sender.into(),
+            amount,
+        },
+    )?;
+    Ok(res.add_submessages(hooks))
 }
 
 fn transfer_from(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     owner: String,
     recipient: String,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
-    // Ensuring balance is unlocked for transaction
-    let balance = BALANCES.load(deps.storage, &info.sender)?;
-    let frozen_balance = FROZEN_BALANCES
-        .load(deps.storage, &info.sender)
-        .unwrap_or(Uint128::zero());
-    if (balance - frozen_balance) < amount {
-        return Err(ContractError::BalanceFrozen {});
-    }
-
-    // ensuring balance capital is not exceeded for an user
+    ensure_memo_not_required(deps.storage, amount)?;
+    ensure_not_blacklisted(deps.storage, &info.sender)?;
+    let owner_addr = deps.api.addr_validate(&owner)?;
     let rcpt_addr = deps.api.addr_validate(&recipient)?;
-    let token_bal = BALANCES
-        .load(deps.storage, &rcpt_addr)
-        .unwrap_or(Uint128::default());
-    let bal_cap = BALANCE_CAP.load(deps.storage)?;
-    if (token_bal + amount) > bal_cap {
-        return Err(ContractError::CannotExceedCap {});
+    let charges = transfer_charges(deps.storage, &owner_addr, &rcpt_addr, amount)?;
+    let net = amount - charges.total();
+
+    // ensuring the balance is unlocked, the transfer policy and KYC requirements let both
+    // parties move tokens and balance capital is not exceeded for them or the fee collector
+    ensure_transfer(deps.storage, &env, &owner_addr, &rcpt_addr, amount, charges)?;
+
+    consume_rate_limit(deps.storage, &env, &owner_addr, amount)?;
+
+    let action = TokenHookMsg::Transfer {
+        sender: owner_addr.to_string(),
+        recipient: rcpt_addr.to_string(),
+        amount,
+    };
+    ensure_policy_allows(deps.as_ref(), action)?;
+
+    // the fee is paid out of the spender's allowance as well
+    if !charges.total().is_zero() {
+        deduct_allowance(
+            deps.storage,
+            &owner_addr,
+            &info.sender,
+            &env.block,
+            charges.total(),
+        )?;
     }
+    charge_transfer_fee(deps.storage, &env, &owner_addr, charges)?;
 
-    Ok(execute_transfer_from(
-        deps, env, info, owner, recipient, amount,
-    )?)
+    let hook = transfer_hook(deps.storage, &rcpt_addr, &owner_addr, net)?;
+    let res = execute_transfer_from(deps.branch(), env.clone(), info, owner, recipient, net)?;
+    checkpoint(deps.storage, &env, &[&owner_addr, &rcpt_addr])?;
+    let hooks = token_hooks(
+        deps.storage,
+        TokenHookMsg::Transfer {
+            sender: owner_addr.into(),
+            recipient: rcpt_addr.into(),
+            amount: net,
+        },
+    )?;
+    Ok(with_charges(res, charges)
+        .add_messages(hook)
+        .add_submessages(hooks))
 }
 
 fn burn_from(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     owner: String,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
-    // Ensuring balance is unlocked for transaction
-    let balance = BALANCES.load(deps.storage, &info.sender)?;
-    let frozen_balance = FROZEN_BALANCES
-        .load(deps.storage, &info.sender)
-        .unwrap_or(Uint128::zero());
-    if (balance - frozen_balance) < amount {
-        return Err(ContractError::BalanceFrozen {});
-    }
+    // Ensuring owner's balance is unlocked for transaction
+    ensure_not_blacklisted(deps.storage, &info.sender)?;
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    ensure_spendable(deps.storage, &env, &owner_addr, amount)?;
+    let action = TokenHookMsg::Burn {
+        owner: owner_addr.to_string(),
+        amount,
+    };
+    ensure_policy_allows(deps.as_ref(), action)?;
 
-    Ok(execute_burn_from(deps, env, info, owner, amount)?)
+    let res = execute_burn_from(deps.branch(), env.clone(), info, owner, amount)?;
+    checkpoint(deps.storage, &env, &[&owner_addr])?;
+    let hooks = token_hooks(
+        deps.storage,
+        TokenHookMsg::Burn {
+            owner: owner_addr.into(),
+            amount,
+        },
+    )?;
+    Ok(res.add_submessages(hooks))
 }
 
 pub fn send_from(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     owner: String,
@@ -300,68 +964,2589 @@ pub fn send_from(
     amount: Uint128,
     msg: Binary,
 ) -> Result<Response, ContractError> {
-    // Ensuring balance is unlocked for transaction
-    let balance = BALANCES.load(deps.storage, &info.sender)?;
-    let frozen_balance = FROZEN_BALANCES
-        .load(deps.storage, &info.sender)
-        .unwrap_or(Uint128::zero());
-    if (balance - frozen_balance) < amount {
-        return Err(ContractError::BalanceFrozen {});
+    ensure_memo_not_required(deps.storage, amount)?;
+    ensure_not_blacklisted(deps.storage, &info.sender)?;
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let contract_addr = deps.api.addr_validate(&contract)?;
+    let charges = transfer_charges(deps.storage, &owner_addr, &contract_addr, amount)?;
+    let net = amount - charges.total();
+
+    // ensuring the balance is unlocked, the transfer policy and KYC requirements let both
+    // parties move tokens and balance capital is not exceeded for them or the fee collector
+    ensure_transfer(
+        deps.storage,
+        &env,
+        &owner_addr,
+        &contract_addr,
+        amount,
+        charges,
+    )?;
+
+    let rate_limited = consume_rate_limit(deps.storage, &env, &owner_addr, amount)?;
+
+    let action = TokenHookMsg::Transfer {
+        sender: owner_addr.to_string(),
+        recipient: contract_addr.to_string(),
+        amount,
+    };
+    ensure_policy_allows(deps.as_ref(), action)?;
+
+    // the fee is paid out of the spender's allowance as well
+    if !charges.total().is_zero() {
+        deduct_allowance(
+            deps.storage,
+            &owner_addr,
+            &info.sender,
+            &env.block,
+            charges.total(),
+        )?;
     }
+    charge_transfer_fee(deps.storage, &env, &owner_addr, charges)?;
 
-    Ok(execute_send_from(
-        deps, env, info, owner, contract, amount, msg,
-    )?)
+    let res = execute_send_from(deps.branch(), env.clone(), info, owner, contract, net, msg)?;
+    checkpoint(deps.storage, &env, &[&owner_addr, &contract_addr])?;
+    let pending = PendingSend {
+        owner: owner_addr,
+        contract: contract_addr,
+        amount: net,
+        fee: None,
+        burned: charges.burned,
+        rate_limited,
+    };
+    track_send(
+        deps.storage,
+        with_charges(res, charges),
+        pending,
+        charges.fee,
+    )
 }
 
-fn update_frozen_list(update_type: UpdateType, deps: DepsMut) -> Result<Response, ContractError> {
-    match update_type {
-        UpdateType::Add(coin) => {
-            let address = deps.api.addr_validate(&coin.address)?;
-            FROZEN_BALANCES.update(
-                deps.storage,
-                &address,
-                |balance: Option<Uint128>| -> StdResult<_> {
-                    Ok(balance.unwrap_or_default().checked_add(coin.amount)?)
-                },
-            )?;
-        }
-        UpdateType::Sub(coin) => {
-            let address = deps.api.addr_validate(&coin.address)?;
-            FROZEN_BALANCES.update(
+/// Moves the fee out of `payer`'s balance over to the fee collector and burns the burn tax.
+/// Runs after every transfer check, see `ensure_transfer`. The caller checkpoints
+/// `payer` afterwards.
+fn charge_transfer_fee(
+    storage: &mut dyn Storage,
+    env: &Env,
+    payer: &Addr,
+    charges: TransferCharges,
+) -> Result<(), ContractError> {
+    if !charges.fee.is_zero() {
+        let collector = TRANSFER_FEE.load(storage)?.collector;
+        move_tokens(storage, payer, &collector, charges.fee)?;
+        checkpoint(storage, env, &[&collector])?;
+    }
+    if !charges.burned.is_zero() {
+        BALANCES.update(storage, payer, |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_sub(charges.burned)?)
+        })?;
+        TOKEN_INFO.update(storage, |mut info| -> StdResult<_> {
+            info.total_supply = info.total_supply.checked_sub(charges.burned)?;
+            Ok(info)
+        })?;
+        let burned = TAX_BURNED.may_load(storage)?.unwrap_or_default();
+        let burned = burned.checked_add(charges.burned).map_err(StdError::from)?;
+        TAX_BURNED.save(storage, &burned)?;
+    }
+    Ok(())
+}
+
+/// Undoes the burn tax `charge_transfer_fee` took from `payer`. Not a mint, so the mint
+/// cap does not apply.
+fn refund_burn_tax(storage: &mut dyn Storage, payer: &Addr, burned: Uint128) -> StdResult<()> {
+    BALANCES.update(storage, payer, |balance: Option<Uint128>| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(burned)?)
+    })?;
+    TOKEN_INFO.update(storage, |mut info| -> StdResult<_> {
+        info.total_supply = info.total_supply.checked_add(burned)?;
+        Ok(info)
+    })?;
+    let total = TAX_BURNED.may_load(storage)?.unwrap_or_default();
+    TAX_BURNED.save(storage, &total.checked_sub(burned)?)
+}
+
+/// Reports the transfer fee and burn tax charged, if any
+fn with_charges(mut res: Response, charges: TransferCharges) -> Response {
+    if !charges.fee.is_zero() {
+        res = res.add_attribute("fee", charges.fee);
+    }
+    if !charges.burned.is_zero() {
+        res = res.add_attribute("burned", charges.burned);
+    }
+    res
+}
+
+/// Dispatches the `Receive` messages of a send as submessages, remembering the send along
+/// with the `fee` charged so that `reply` can undo all of it if the receiving contract
+/// fails. Uses `reply_always` rather than `reply_on_error`: the token hooks are only told
+/// about the send once it succeeded, and the pending send must not linger in storage.
+fn track_send(
+    storage: &mut dyn Storage,
+    mut res: Response,
+    mut pending: PendingSend,
+    fee: Uint128,
+) -> Result<Response, ContractError> {
+    if !fee.is_zero() {
+        let collector = TRANSFER_FEE.load(storage)?.collector;
+        pending.fee = Some((collector, fee));
+    }
+    let id = NEXT_SEND_ID.may_load(storage)?.unwrap_or_default();
+    NEXT_SEND_ID.save(storage, &(id + 1))?;
+    PENDING_SENDS.save(storage, U64Key::from(id), &pending)?;
+
+    res.messages = res
+        .messages
+        .into_iter()
+        .map(|sub_msg| SubMsg::reply_always(sub_msg.msg, id))
+        .collect();
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    // a failing token hook only reverts its own changes, the token movement goes through
+    if msg.id == TOKEN_HOOK_REPLY_ID {
+        let err = match msg.result {
+            ContractResult::Ok(_) => String::new(),
+            ContractResult::Err(err) => err,
+        };
+        let event = Event::new("token_hook_failed").add_attribute("error", err);
+        return Ok(Response::new().add_event(event));
+    }
+
+    let pending = PENDING_SENDS.load(deps.storage, U64Key::from(msg.id))?;
+    PENDING_SENDS.remove(deps.storage, U64Key::from(msg.id));
+
+    let err = match msg.result {
+        ContractResult::Ok(_) => {
+            let hooks = token_hooks(
                 deps.storage,
-                &address,
-                |balance: Option<Uint128>| -> StdResult<_> {
-                    Ok(balance.unwrap_or_default().checked_sub(coin.amount)?)
+                TokenHookMsg::Transfer {
+                    sender: pending.owner.into(),
+                    recipient: pending.contract.into(),
+                    amount: pending.amount,
                 },
             )?;
+            return Ok(Response::new().add_submessages(hooks));
         }
-        UpdateType::Discard(addr) => {
-            let address = deps.api.addr_validate(&addr)?;
-            FROZEN_BALANCES.remove(deps.storage, &address)
-        }
+        ContractResult::Err(err) => err,
     };
 
-    let res = Response::new().add_attribute("action", "update_frozen_list");
-    Ok(res)
-}
+    // the receiving contract failed, handing the tokens, the fee and the burn tax back to
+    // their owner without asking the policy contract, which already allowed the send
+    move_tokens(
+        deps.storage,
+        &pending.contract,
+        &pending.owner,
+        pending.amount,
+    )?;
+    let mut refunded = vec![&pending.contract, &pending.owner];
+    if let Some((collector, fee)) = &pending.fee {
+        move_tokens(deps.storage, collector, &pending.owner, *fee)?;
+        refunded.push(collector);
+    }
+    if !pending.burned.is_zero() {
+        refund_burn_tax(deps.storage, &pending.owner, pending.burned)?;
+    }
+    restore_rate_limit(deps.storage, &pending.owner, pending.rate_limited)?;
+    checkpoint(deps.storage, &env, &refunded)?;
+    if !pending.burned.is_zero() {
+        // the tax was never burned after all, rather than burned and minted again
+        let mut stats = SUPPLY_STATS.load(deps.storage)?;
+        stats.minted = stats
+            .minted
+            .checked_sub(pending.burned)
+            .map_err(StdError::from)?;
+        stats.burned = stats
+            .burned
+            .checked_sub(pending.burned)
+            .map_err(StdError::from)?;
+        SUPPLY_STATS.save(deps.storage, &stats)?;
+    }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: Query) -> StdResult<Binary> {
-    match msg {
-        // inherited from cw20-base
-        Query::TokenInfo {} => to_binary(&query_token_info(deps)?),
-        Query::Balance { address } => to_binary(&query_balance(deps, address)?),
-        Query::FrozenBalance { address } => to_binary(&query_frozen_balance(deps, address)?),
-        Query::Allowance { owner, spender } => to_binary(&query_allowance(deps, owner, spender)?),
-        Query::Minter {} => to_binary(&query_minter(deps)?),
+    let mut event = Event::new("send_failed")
+        .add_attribute("owner", &pending.owner)
+        .add_attribute("contract", &pending.contract)
+        .add_attribute("amount", pending.amount);
+    if let Some((_, fee)) = pending.fee {
+        event = event.add_attribute("fee", fee);
     }
+    if !pending.burned.is_zero() {
+        event = event.add_attribute("burned", pending.burned);
+    }
+    Ok(Response::new().add_event(event.add_attribute("error", err)))
 }
 
-pub fn query_frozen_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
-    let address = deps.api.addr_validate(&address)?;
+#[allow(clippy::too_many_arguments)]
+fn permit_allowance(
+    deps: DepsMut,
+    env: Env,
+    owner: String,
+    spender: String,
+    amount: Uint128,
+    nonce: u64,
+    expires: Expiration,
+    pubkey: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    if expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    if owner_addr == spender_addr {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+
+    let expected = PERMIT_NONCES
+        .may_load(deps.storage, &owner_addr)?
+        .unwrap_or_default();
+    if nonce != expected {
+        return Err(ContractError::InvalidNonce { expected });
+    }
+
+    let payload = PermitPayload {
+        chain_id: env.block.chain_id,
+        contract: env.contract.address.into(),
+        owner,
+        spender,
+        amount,
+        nonce,
+        expires,
+    };
+    verify_signature(deps.api, &owner_addr, &pubkey, &payload, &signature)?;
+    PERMIT_NONCES.save(deps.storage, &owner_addr, &(nonce + 1))?;
+
+    let allowance = AllowanceResponse {
+        allowance: amount,
+        expires,
+    };
+    ALLOWANCES.save(deps.storage, (&owner_addr, &spender_addr), &allowance)?;
+
+    let res = Response::new()
+        .add_attribute("action", "permit_allowance")
+        .add_attribute("owner", owner_addr)
+        .add_attribute("spender", spender_addr)
+        .add_attribute("amount", amount)
+        .add_attribute("nonce", nonce.to_string());
+    Ok(res)
+}
+
+fn permit_and_call(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    permit: Permit,
+    call: Binary,
+    contract: String,
+) -> Result<Response, ContractError> {
+    if deps.api.addr_validate(&permit.spender)? != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let owner = permit.owner.clone();
+    let (amount, nonce) = (permit.amount, permit.nonce);
+    permit_allowance(
+        deps.branch(),
+        env.clone(),
+        permit.owner,
+        permit.spender,
+        amount,
+        nonce,
+        permit.expires,
+        permit.pubkey,
+        permit.signature,
+    )?;
+    let res = send_from(deps, env, info, owner, contract, amount, call)?;
+    Ok(res.add_attribute("nonce", nonce.to_string()))
+}
+
+fn authorize_router(
+    deps: DepsMut,
+    info: MessageInfo,
+    router: String,
+    max_per_tx: Uint128,
+    expiry: Expiration,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let router = deps.api.addr_validate(&router)?;
+    ROUTERS.save(deps.storage, &router, &RouterConfig { max_per_tx, expiry })?;
+
+    let res = Response::new()
+        .add_attribute("action", "authorize_router")
+        .add_attribute("router", router)
+        .add_attribute("max_per_tx", max_per_tx)
+        .add_attribute("expiry", expiry.to_string());
+    Ok(res)
+}
+
+fn revoke_router(
+    deps: DepsMut,
+    info: MessageInfo,
+    router: String,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let router = deps.api.addr_validate(&router)?;
+    ROUTERS.remove(deps.storage, &router);
+
+    let res = Response::new()
+        .add_attribute("action", "revoke_router")
+        .add_attribute("router", router);
+    Ok(res)
+}
+
+fn opt_in_router(
+    deps: DepsMut,
+    info: MessageInfo,
+    router: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let router = deps.api.addr_validate(&router)?;
+    if amount.is_zero() {
+        ROUTER_ALLOWANCES.remove(deps.storage, (&info.sender, &router));
+    } else {
+        ROUTER_ALLOWANCES.save(deps.storage, (&info.sender, &router), &amount)?;
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "opt_in_router")
+        .add_attribute("owner", info.sender)
+        .add_attribute("router", router)
+        .add_attribute("opted_in", (!amount.is_zero()).to_string())
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+fn router_transfer_from(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config = ROUTERS
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+    if config.expiry.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    if amount > config.max_per_tx {
+        return Err(ContractError::RouterLimitExceeded {});
+    }
+    ensure_not_blacklisted(deps.storage, &info.sender)?;
+    if ACCOUNT_FROZEN.has(deps.storage, &info.sender) {
+        return Err(ContractError::AccountFrozen {});
+    }
+
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let allowance = ROUTER_ALLOWANCES
+        .may_load(deps.storage, (&owner_addr, &info.sender))?
+        .ok_or(ContractError::NoAllowance {})?;
+    let remaining = allowance
+        .checked_sub(amount)
+        .map_err(|_| ContractError::RouterAllowanceExceeded {})?;
+
+    // moving the tokens as if the owner sent them, so all transfer checks apply
+    let owner_info = MessageInfo {
+        sender: owner_addr.clone(),
+        funds: vec![],
+    };
+    let res = transfer(deps.branch(), env, owner_info, recipient, amount)?;
+    let key = (&owner_addr, &info.sender);
+    if remaining.is_zero() {
+        ROUTER_ALLOWANCES.remove(deps.storage, key);
+    } else {
+        ROUTER_ALLOWANCES.save(deps.storage, key, &remaining)?;
+    }
+    Ok(res.add_attribute("by", info.sender))
+}
+
+fn update_frozen_list(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    update_type: UpdateType,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Compliance)?;
+
+    match update_type {
+        UpdateType::Add(coin) => {
+            let address = deps.api.addr_validate(&coin.address)?;
+            FROZEN_BALANCES.update(
+                deps.storage,
+                &address,
+                |balance: Option<Uint128>| -> StdResult<_> {
+                    Ok(balance.unwrap_or_default().checked_add(coin.amount)?)
+                },
+            )?;
+            update_frozen_total(deps.storage, Uint128::zero(), coin.amount)?;
+        }
+        UpdateType::Sub(coin) => {
+            let address = deps.api.addr_validate(&coin.address)?;
+            FROZEN_BALANCES.update(
+                deps.storage,
+                &address,
+                |balance: Option<Uint128>| -> StdResult<_> {
+                    Ok(balance.unwrap_or_default().checked_sub(coin.amount)?)
+                },
+            )?;
+            update_frozen_total(deps.storage, coin.amount, Uint128::zero())?;
+        }
+        UpdateType::Discard(addr) => {
+            let address = deps.api.addr_validate(&addr)?;
+            if let Some(frozen) = FROZEN_BALANCES.may_load(deps.storage, &address)? {
+                update_frozen_total(deps.storage, frozen, Uint128::zero())?;
+            }
+            FROZEN_BALANCES.remove(deps.storage, &address)
+        }
+        UpdateType::Lock {
+            address,
+            amount,
+            unlock_at,
+        } => {
+            let address = deps.api.addr_validate(&address)?;
+            let now = env.block.time.seconds();
+            if unlock_at <= now {
+                return Err(ContractError::Expired {});
+            }
+            // expired tranches are dropped whenever a new one is added
+            let mut tranches = active_tranches(
+                FROZEN_TRANCHES
+                    .may_load(deps.storage, &address)?
+                    .unwrap_or_default(),
+                now,
+            );
+            tranches.push(FrozenTranche { amount, unlock_at });
+            FROZEN_TRANCHES.save(deps.storage, &address, &tranches)?;
+        }
+    };
+
+    let res = Response::new().add_attribute("action", "update_frozen_list");
+    Ok(res)
+}
+
+fn ensure_role(deps: Deps, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if !ROLES.has(deps.storage, (sender, role.key())) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+fn update_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+    granted: bool,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    apply_role(deps, address, role, granted)
+}
+
+fn apply_role(
+    deps: DepsMut,
+    address: String,
+    role: Role,
+    granted: bool,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let action = if granted {
+        ROLES.save(deps.storage, (&address, role.key()), &true)?;
+        "grant_role"
+    } else {
+        ROLES.remove(deps.storage, (&address, role.key()));
+        "revoke_role"
+    };
+
+    let res = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("address", address)
+        .add_attribute("role", format!("{:?}", role));
+    Ok(res)
+}
+
+fn lock_self(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    until: u64,
+) -> Result<Response, ContractError> {
+    if until <= env.block.time.seconds() {
+        return Err(ContractError::Expired {});
+    }
+    // a compromised key must not be able to shorten the lock
+    if let Some(current) = SELF_LOCKS.may_load(deps.storage, &info.sender)? {
+        if until < current {
+            return Err(ContractError::CannotShortenLock {});
+        }
+    }
+    SELF_LOCKS.save(deps.storage, &info.sender, &until)?;
+
+    let res = Response::new()
+        .add_attribute("action", "lock_self")
+        .add_attribute("address", info.sender)
+        .add_attribute("until", until.to_string());
+    Ok(res)
+}
+
+fn unlock_self(deps: DepsMut, info: MessageInfo, owner: String) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    match RECOVERY_ADDRESSES.may_load(deps.storage, &owner)? {
+        Some(recovery) if recovery == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+    SELF_LOCKS.remove(deps.storage, &owner);
+
+    let res = Response::new()
+        .add_attribute("action", "unlock_self")
+        .add_attribute("address", owner)
+        .add_attribute("by", info.sender);
+    Ok(res)
+}
+
+fn set_transfer_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    bps: u16,
+    collector: String,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::FeeManager)?;
+    ensure_not_timelocked(deps.storage)?;
+
+    apply_transfer_fee(deps, bps, collector)
+}
+
+fn apply_transfer_fee(
+    deps: DepsMut,
+    bps: u16,
+    collector: String,
+) -> Result<Response, ContractError> {
+    let burn_rate = BURN_RATE.may_load(deps.storage)?.unwrap_or_default();
+    if u32::from(bps) + u32::from(burn_rate) > 10_000 {
+        return Err(ContractError::InvalidTransferFee {});
+    }
+
+    let collector = deps.api.addr_validate(&collector)?;
+    if bps == 0 {
+        TRANSFER_FEE.remove(deps.storage);
+    } else {
+        let config = TransferFeeConfig {
+            bps,
+            collector: collector.clone(),
+        };
+        TRANSFER_FEE.save(deps.storage, &config)?;
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "set_transfer_fee")
+        .add_attribute("bps", bps.to_string())
+        .add_attribute("collector", collector);
+    Ok(res)
+}
+
+fn set_burn_rate(deps: DepsMut, info: MessageInfo, bps: u16) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::FeeManager)?;
+    ensure_not_timelocked(deps.storage)?;
+
+    apply_burn_rate(deps.storage, bps)
+}
+
+fn apply_burn_rate(storage: &mut dyn Storage, bps: u16) -> Result<Response, ContractError> {
+    let fee = TRANSFER_FEE
+        .may_load(storage)?
+        .map_or(0, |config| config.bps);
+    if u32::from(bps) + u32::from(fee) > 10_000 {
+        return Err(ContractError::InvalidTransferFee {});
+    }
+
+    if bps == 0 {
+        BURN_RATE.remove(storage);
+    } else {
+        BURN_RATE.save(storage, &bps)?;
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "set_burn_rate")
+        .add_attribute("bps", bps.to_string());
+    Ok(res)
+}
+
+fn set_fee_exemption(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    exempt: bool,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::FeeManager)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    if exempt {
+        FEE_EXEMPTIONS.save(deps.storage, &address, &true)?;
+    } else {
+        FEE_EXEMPTIONS.remove(deps.storage, &address);
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "set_fee_exemption")
+        .add_attribute("address", address)
+        .add_attribute("exempt", exempt.to_string());
+    Ok(res)
+}
+
+fn register_merkle_root(
+    deps: DepsMut,
+    info: MessageInfo,
+    merkle_root: String,
+    total_amount: Uint128,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+    let mut root = [0u8; 32];
+    hex::decode_to_slice(&merkle_root, &mut root)
+        .map_err(|_| ContractError::InvalidMerkleRoot {})?;
+
+    let stage = AIRDROP_LATEST_STAGE
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        + 1;
+    AIRDROP_LATEST_STAGE.save(deps.storage, &stage)?;
+    AIRDROP_STAGES.save(
+        deps.storage,
+        U8Key::from(stage),
+        &AirdropStage {
+            merkle_root: merkle_root.clone(),
+            total_amount,
+            claimed_amount: Uint128::zero(),
+        },
+    )?;
+
+    let res = Response::new()
+        .add_attribute("action", "register_merkle_root")
+        .add_attribute("stage", stage.to_string())
+        .add_attribute("merkle_root", merkle_root)
+        .add_attribute("total_amount", total_amount);
+    Ok(res)
+}
+
+fn claim_airdrop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    stage: u8,
+    amount: Uint128,
+    proof: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut airdrop = AIRDROP_STAGES.load(deps.storage, U8Key::from(stage))?;
+    if AIRDROP_CLAIMS.has(deps.storage, (&info.sender, U8Key::from(stage))) {
+        return Err(ContractError::AirdropClaimed {});
+    }
+    verify_proof(
+        &airdrop.merkle_root,
+        info.sender.as_str(),
+        &amount.to_string(),
+        &proof,
+    )?;
+
+    // ensuring balance capital is not exceeded for the claimant
+    ensure_mint(deps.storage, &info.sender, amount)?;
+    let action = TokenHookMsg::Mint {
+        recipient: info.sender.to_string(),
+        amount,
+    };
+    ensure_policy_allows(deps.as_ref(), action)?;
+
+    airdrop.claimed_amount = match airdrop.claimed_amount.checked_add(amount) {
+        Ok(claimed) if claimed <= airdrop.total_amount => claimed,
+        _ => return Err(ContractError::AirdropExhausted {}),
+    };
+    AIRDROP_STAGES.save(deps.storage, U8Key::from(stage), &airdrop)?;
+    AIRDROP_CLAIMS.save(deps.storage, (&info.sender, U8Key::from(stage)), &true)?;
+
+    mint_tokens(deps.storage, &info.sender, amount)?;
+    checkpoint(deps.storage, &env, &[&info.sender])?;
+    let hooks = token_hooks(
+        deps.storage,
+        TokenHookMsg::Mint {
+            recipient: info.sender.to_string(),
+            amount,
+        },
+    )?;
+
+    let res = Response::new()
+        .add_attribute("action", "claim_airdrop")
+        .add_attribute("stage", stage.to_string())
+        .add_attribute("address", info.sender)
+        .add_attribute("amount", amount)
+        .add_submessages(hooks);
+    Ok(res)
+}
+
+fn stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    // Ensuring balance is unlocked for staking
+    ensure_spendable(deps.storage, &env, &info.sender, amount)?;
+
+    // the stake stays owned by the sender, so this is not a transfer for the policy contract
+    let contract = env.contract.address.clone();
+    move_tokens(deps.storage, &info.sender, &contract, amount)?;
+    checkpoint(deps.storage, &env, &[&info.sender, &contract])?;
+    let stake = STAKES.update(deps.storage, &info.sender, |stake| -> StdResult<_> {
+        Ok(stake.unwrap_or_default().checked_add(amount)?)
+    })?;
+    let staked = TOTAL_STAKED
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .checked_add(amount)
+        .map_err(StdError::from)?;
+    TOTAL_STAKED.save(deps.storage, &staked)?;
+
+    let res = Response::new()
+        .add_attribute("action", "stake")
+        .add_attribute("address", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("stake", stake);
+    Ok(res)
+}
+
+fn unstake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // the stake earned rewards up to now, unbonding tokens no longer do
+    let balance = BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let earning = reward_weight(deps.storage, &info.sender, balance)?;
+    settle_rewards(deps.storage, &info.sender, earning)?;
+    let stake = STAKES.update(deps.storage, &info.sender, |stake| -> StdResult<_> {
+        Ok(stake.unwrap_or_default().checked_sub(amount)?)
+    })?;
+    let staked = TOTAL_STAKED
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .checked_sub(amount)
+        .map_err(StdError::from)?;
+    TOTAL_STAKED.save(deps.storage, &staked)?;
+    let config = STAKING_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let release_at = Expiration::AtTime(env.block.time.plus_seconds(config.unbonding_period));
+    CLAIMS.create_claim(deps.storage, &info.sender, amount, release_at)?;
+
+    let res = Response::new()
+        .add_attribute("action", "unstake")
+        .add_attribute("address", info.sender)
+        .add_attribute("amount", amount)
+        .add_attribute("stake", stake)
+        .add_attribute("release_at", release_at.to_string());
+    Ok(res)
+}
+
+fn claim_unstaked(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let amount = CLAIMS.claim_tokens(deps.storage, &info.sender, &env.block, None)?;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    // ensuring balance capital is not exceeded for the claimant
+    ensure_cap(deps.storage, &info.sender, amount)?;
+
+    // returning escrowed tokens to their owner is exempt from the policy contract
+    let contract = env.contract.address.clone();
+    move_tokens(deps.storage, &contract, &info.sender, amount)?;
+    checkpoint(deps.storage, &env, &[&contract, &info.sender])?;
+
+    let res = Response::new()
+        .add_attribute("action", "claim_unstaked")
+        .add_attribute("address", info.sender)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+/// Moves `amount` from `from` to `to` without any of the transfer checks
+fn move_tokens(
+    storage: &mut dyn Storage,
+    from: &Addr,
+    to: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    BALANCES.update(storage, from, |balance: Option<Uint128>| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_sub(amount)?)
+    })?;
+    BALANCES.update(storage, to, |balance: Option<Uint128>| -> StdResult<_> {
+        Ok(balance.unwrap_or_default() + amount)
+    })?;
+    Ok(())
+}
+
+fn update_staking_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    unbonding_period: u64,
+    rate_limit_boost: Decimal,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let config = StakingConfig {
+        unbonding_period,
+        rate_limit_boost,
+    };
+    STAKING_CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_staking_config")
+        .add_attribute("unbonding_period", unbonding_period.to_string())
+        .add_attribute("rate_limit_boost", rate_limit_boost.to_string());
+    Ok(res)
+}
+
+fn set_reward_denom(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+    if REWARD_INDEX.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::RewardsAlreadyDistributed {});
+    }
+
+    REWARD_DENOM.save(deps.storage, &denom)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_reward_denom")
+        .add_attribute("denom", denom);
+    Ok(res)
+}
+
+fn distribute_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let denom = REWARD_DENOM
+        .may_load(deps.storage)?
+        .ok_or(ContractError::RewardDenomNotSet {})?;
+    let sent = info
+        .funds
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .sum::<Uint128>();
+    if sent.is_zero() {
+        return Err(ContractError::EmptyBalance { denom });
+    }
+    if sent != amount {
+        return Err(ContractError::RewardFundsMismatch {});
+    }
+
+    // only what someone can claim rewards on counts, so nothing is left stranded
+    let earning = earning_supply(deps.storage, &env)?;
+    if earning.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // holders settle against the index lazily, so nobody is iterated here
+    let index = REWARD_INDEX.may_load(deps.storage)?.unwrap_or_default()
+        + Decimal::from_ratio(amount, earning);
+    REWARD_INDEX.save(deps.storage, &index)?;
+
+    let res = Response::new()
+        .add_attribute("action", "distribute_rewards")
+        .add_attribute("amount", amount)
+        .add_attribute("denom", denom)
+        .add_attribute("index", index.to_string());
+    Ok(res)
+}
+
+fn claim_rewards(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let denom = REWARD_DENOM
+        .may_load(deps.storage)?
+        .ok_or(ContractError::RewardDenomNotSet {})?;
+    let balance = BALANCES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let earning = reward_weight(deps.storage, &info.sender, balance)?;
+    let mut rewards = settle_rewards(deps.storage, &info.sender, earning)?;
+    if rewards.pending.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let amount = rewards.pending;
+    rewards.pending = Uint128::zero();
+    USER_REWARDS.save(deps.storage, &info.sender, &rewards)?;
+
+    let res = Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin::new(amount.u128(), denom)],
+        })
+        .add_attribute("action", "claim_rewards")
+        .add_attribute("address", info.sender)
+        .add_attribute("amount", amount);
+    Ok(res)
+}
+
+fn delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegatee: String,
+) -> Result<Response, ContractError> {
+    let delegatee = deps.api.addr_validate(&delegatee)?;
+    let previous = delegate_of(deps.storage, &info.sender)?;
+    if delegatee == info.sender {
+        DELEGATES.remove(deps.storage, &info.sender);
+    } else {
+        DELEGATES.save(deps.storage, &info.sender, &delegatee)?;
+    }
+
+    // the votes follow the balance as it is now, later changes go through checkpoint
+    let balance = BALANCE_SNAPSHOTS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    let height = env.block.height;
+    VOTING_POWER.update(deps.storage, &previous, height, |power| -> StdResult<_> {
+        Ok(power.unwrap_or_default().checked_sub(balance)?)
+    })?;
+    VOTING_POWER.update(deps.storage, &delegatee, height, |power| -> StdResult<_> {
+        Ok(power.unwrap_or_default().checked_add(balance)?)
+    })?;
+
+    let res = Response::new()
+        .add_attribute("action", "delegate")
+        .add_attribute("delegator", info.sender)
+        .add_attribute("from_delegate", previous)
+        .add_attribute("to_delegate", delegatee)
+        .add_attribute("amount", balance);
+    Ok(res)
+}
+
+fn set_recovery_address(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: Option<String>,
+) -> Result<Response, ContractError> {
+    if let Some(until) = SELF_LOCKS.may_load(deps.storage, &info.sender)? {
+        if env.block.time.seconds() < until {
+            return Err(ContractError::AccountLocked { until });
+        }
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "set_recovery_address")
+        .add_attribute("address", info.sender.clone());
+    match address {
+        Some(address) => {
+            let recovery = deps.api.addr_validate(&address)?;
+            RECOVERY_ADDRESSES.save(deps.storage, &info.sender, &recovery)?;
+            Ok(res.add_attribute("recovery_address", recovery))
+        }
+        None => {
+            RECOVERY_ADDRESSES.remove(deps.storage, &info.sender);
+            Ok(res)
+        }
+    }
+}
+
+/// Returns the `TransferHook` message to send to `recipient` if it registered for one
+fn transfer_hook(
+    storage: &dyn Storage,
+    recipient: &Addr,
+    sender: &Addr,
+    amount: Uint128,
+) -> StdResult<Option<CosmosMsg>> {
+    if !TRANSFER_HOOKS.has(storage, recipient) {
+        return Ok(None);
+    }
+    let msg = TransferHookMsg {
+        sender: sender.to_string(),
+        amount,
+    };
+    Ok(Some(msg.into_cosmos_msg(recipient)?))
+}
+
+/// Fails unless the policy contract, if one is set, allows `action`. An unreachable policy
+/// contract lets actions through only when configured to fail open.
+///
+/// Every mint, burn and transfer a holder or minter initiates is checked. Token movements
+/// that do not change who owns the tokens or that a policy must not be able to block are
+/// exempt: staking escrow and unbonding claims, transfer fees and burn tax (charged as part
+/// of an allowed transfer), refunds of failed sends and compliance clawbacks.
+fn ensure_policy_allows(deps: Deps, action: TokenHookMsg) -> Result<(), ContractError> {
+    let policy = match POLICY_CONTRACT.may_load(deps.storage)? {
+        Some(policy) => policy,
+        None => return Ok(()),
+    };
+
+    let query = PolicyQueryMsg::CheckPolicy { action };
+    match deps
+        .querier
+        .query_wasm_smart::<PolicyResponse>(&policy.address, &query)
+    {
+        Ok(res) if res.allowed => Ok(()),
+        Ok(res) => Err(ContractError::PolicyDenied {
+            reason: res.reason.unwrap_or_default(),
+        }),
+        Err(_) if policy.fail_open => Ok(()),
+        Err(_) => Err(ContractError::PolicyUnreachable {}),
+    }
+}
+
+fn set_policy_contract(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: Option<String>,
+    fail_open: bool,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let mut res = Response::new().add_attribute("action", "set_policy_contract");
+    match address {
+        Some(address) => {
+            let address = deps.api.addr_validate(&address)?;
+            let policy = PolicyContract {
+                address: address.clone(),
+                fail_open,
+            };
+            POLICY_CONTRACT.save(deps.storage, &policy)?;
+            res = res
+                .add_attribute("address", address)
+                .add_attribute("fail_open", fail_open.to_string());
+        }
+        None => POLICY_CONTRACT.remove(deps.storage),
+    }
+    Ok(res)
+}
+
+/// Notifies every registered token hook of `msg`, failures being caught by `reply`
+fn token_hooks(storage: &dyn Storage, msg: TokenHookMsg) -> StdResult<Vec<SubMsg>> {
+    TOKEN_HOOKS.prepare_hooks(storage, |hook| {
+        let msg = msg.clone().into_cosmos_msg(hook)?;
+        Ok(SubMsg::reply_on_error(msg, TOKEN_HOOK_REPLY_ID))
+    })
+}
+
+fn add_token_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    TOKEN_HOOKS.add_hook(deps.storage, address.clone())?;
+
+    let res = Response::new()
+        .add_attribute("action", "add_token_hook")
+        .add_attribute("hook", address);
+    Ok(res)
+}
+
+fn remove_token_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    TOKEN_HOOKS.remove_hook(deps.storage, address.clone())?;
+
+    let res = Response::new()
+        .add_attribute("action", "remove_token_hook")
+        .add_attribute("hook", address);
+    Ok(res)
+}
+
+fn register_transfer_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    register: bool,
+) -> Result<Response, ContractError> {
+    let action = if register {
+        TRANSFER_HOOKS.save(deps.storage, &info.sender, &true)?;
+        "register_transfer_hook"
+    } else {
+        TRANSFER_HOOKS.remove(deps.storage, &info.sender);
+        "unregister_transfer_hook"
+    };
+
+    let res = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("address", info.sender);
+    Ok(res)
+}
+
+/// Records `amount` as moved out by `address` within its current window, rejecting it
+/// if the configured rate limit would be exceeded. Returns the amount recorded, nothing
+/// when `address` is not rate limited.
+fn consume_rate_limit(
+    storage: &mut dyn Storage,
+    env: &Env,
+    address: &Addr,
+    amount: Uint128,
+) -> Result<Uint128, ContractError> {
+    let limit = match RATE_LIMIT.may_load(storage)? {
+        Some(limit) => limit,
+        None => return Ok(Uint128::zero()),
+    };
+    let exempt = RATE_LIMIT_EXEMPTIONS
+        .may_load(storage, address)?
+        .unwrap_or_default();
+    let staked = STAKES.may_load(storage, address)?.unwrap_or_default();
+    let boost = STAKING_CONFIG
+        .may_load(storage)?
+        .unwrap_or_default()
+        .rate_limit_boost;
+    let window = RATE_LIMITS.may_load(storage, address)?.unwrap_or_default();
+    let now = env.block.time.seconds();
+    match logic::consume_rate_limit(window, limit, exempt, staked, boost, now, amount)? {
+        Some(window) => RATE_LIMITS.save(storage, address, &window)?,
+        None => return Ok(Uint128::zero()),
+    }
+
+    Ok(amount)
+}
+
+/// Gives `amount` recorded by `consume_rate_limit` back to `address`. Replies run in the
+/// same block, so the window it was recorded in is still the current one.
+fn restore_rate_limit(storage: &mut dyn Storage, address: &Addr, amount: Uint128) -> StdResult<()> {
+    if amount.is_zero() {
+        return Ok(());
+    }
+    RATE_LIMITS.update(storage, address, |window| -> StdResult<_> {
+        let mut window = window.unwrap_or_default();
+        window.consumed = window.consumed.saturating_sub(amount);
+        Ok(window)
+    })?;
+    Ok(())
+}
+
+/// Returns `limit` raised by the staking boost of `address`
+fn staked_limit(storage: &dyn Storage, address: &Addr, limit: Uint128) -> StdResult<Uint128> {
+    let config = STAKING_CONFIG.may_load(storage)?.unwrap_or_default();
+    let staked = STAKES.may_load(storage, address)?.unwrap_or_default();
+    Ok(boosted_limit(limit, staked, config.rate_limit_boost))
+}
+
+fn update_balance_cap(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_cap: Uint128,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+    ensure_not_timelocked(deps.storage)?;
+
+    apply_balance_cap(deps.storage, new_cap)
+}
+
+/// Sets the balance cap for both the admin and the governance path. No per-use minimum
+/// is configured for the cap to stay above: the rate limit, router limits and minter caps
+/// are upper bounds and the KYC and travel rule thresholds only add requirements, so only
+/// a zero cap is rejected.
+fn apply_balance_cap(
+    storage: &mut dyn Storage,
+    new_cap: Uint128,
+) -> Result<Response, ContractError> {
+    if new_cap.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let old_cap = BALANCE_CAP.load(storage)?;
+    BALANCE_CAP.save(storage, &new_cap)?;
+
+    let res = Response::new()
+        .add_attribute("action", "update_balance_cap")
+        .add_attribute("old_cap", old_cap)
+        .add_attribute("new_cap", new_cap);
+    Ok(res)
+}
+
+fn set_rate_limit(
+    deps: DepsMut,
+    info: MessageInfo,
+    limit: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+    ensure_not_timelocked(deps.storage)?;
+
+    apply_rate_limit(deps.storage, limit)
+}
+
+fn apply_rate_limit(
+    storage: &mut dyn Storage,
+    limit: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    match limit {
+        Some(limit) => RATE_LIMIT.save(storage, &limit)?,
+        None => RATE_LIMIT.remove(storage),
+    }
+
+    let res = Response::new().add_attribute("action", "set_rate_limit");
+    Ok(res)
+}
+
+fn set_timelock_delay(
+    deps: DepsMut,
+    info: MessageInfo,
+    delay: u64,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+    ensure_not_timelocked(deps.storage)?;
+
+    apply_timelock_delay(deps.storage, delay)
+}
+
+fn apply_timelock_delay(storage: &mut dyn Storage, delay: u64) -> Result<Response, ContractError> {
+    TIMELOCK_DELAY.save(storage, &delay)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_timelock_delay")
+        .add_attribute("delay", delay.to_string());
+    Ok(res)
+}
+
+fn set_transfer_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    policy: TransferPolicy,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+    ensure_not_timelocked(deps.storage)?;
+
+    apply_transfer_policy(deps.storage, policy)
+}
+
+fn apply_transfer_policy(
+    storage: &mut dyn Storage,
+    policy: TransferPolicy,
+) -> Result<Response, ContractError> {
+    TRANSFER_POLICY.save(storage, &policy)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_transfer_policy")
+        .add_attribute("policy", format!("{:?}", policy));
+    Ok(res)
+}
+
+/// Fails if a timelock delay is set, in which case the action has to be scheduled
+fn ensure_not_timelocked(storage: &dyn Storage) -> Result<(), ContractError> {
+    if TIMELOCK_DELAY.may_load(storage)?.unwrap_or_default() > 0 {
+        return Err(ContractError::TimelockRequired {});
+    }
+    Ok(())
+}
+
+fn schedule(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: ScheduledAction,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let id = NEXT_ACTION_ID.may_load(deps.storage)?.unwrap_or_default();
+    NEXT_ACTION_ID.save(deps.storage, &(id + 1))?;
+
+    let delay = TIMELOCK_DELAY.may_load(deps.storage)?.unwrap_or_default();
+    let eta = env.block.time.seconds() + delay;
+    PENDING_ACTIONS.save(
+        deps.storage,
+        U64Key::from(id),
+        &PendingAction { id, action, eta },
+    )?;
+
+    let res = Response::new()
+        .add_attribute("action", "schedule")
+        .add_attribute("id", id.to_string())
+        .add_attribute("eta", eta.to_string());
+    Ok(res)
+}
+
+fn execute_scheduled(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let pending = PENDING_ACTIONS.load(deps.storage, U64Key::from(id))?;
+    if env.block.time.seconds() < pending.eta {
+        return Err(ContractError::TimelockNotReady { eta: pending.eta });
+    }
+    PENDING_ACTIONS.remove(deps.storage, U64Key::from(id));
+
+    let res = match pending.action {
+        ScheduledAction::UpdateBalanceCap { new_cap } => apply_balance_cap(deps.storage, new_cap)?,
+        ScheduledAction::SetRateLimit { limit } => apply_rate_limit(deps.storage, limit)?,
+        ScheduledAction::SetTimelockDelay { delay } => apply_timelock_delay(deps.storage, delay)?,
+        ScheduledAction::SetTransferPolicy { policy } => {
+            apply_transfer_policy(deps.storage, policy)?
+        }
+        ScheduledAction::SetTransferFee { bps, collector } => {
+            apply_transfer_fee(deps, bps, collector)?
+        }
+        ScheduledAction::SetBurnRate { bps } => apply_burn_rate(deps.storage, bps)?,
+    };
+    Ok(res.add_attribute("scheduled_id", id.to_string()))
+}
+
+fn cancel_scheduled(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    PENDING_ACTIONS.load(deps.storage, U64Key::from(id))?;
+    PENDING_ACTIONS.remove(deps.storage, U64Key::from(id));
+
+    let res = Response::new()
+        .add_attribute("action", "cancel_scheduled")
+        .add_attribute("id", id.to_string());
+    Ok(res)
+}
+
+fn set_rate_limit_exemption(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    exempt: bool,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    if exempt {
+        RATE_LIMIT_EXEMPTIONS.save(deps.storage, &address, &true)?;
+    } else {
+        RATE_LIMIT_EXEMPTIONS.remove(deps.storage, &address);
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "set_rate_limit_exemption")
+        .add_attribute("address", address)
+        .add_attribute("exempt", exempt.to_string());
+    Ok(res)
+}
+
+fn set_burn_address(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    burn: bool,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    // rewards earned so far are kept, burn addresses earn nothing from now on
+    let balance = BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let earning = reward_weight(deps.storage, &address, balance)?;
+    settle_rewards(deps.storage, &address, earning)?;
+    if burn {
+        BURN_ADDRESSES.save(deps.storage, &address, &true)?;
+    } else {
+        BURN_ADDRESSES.remove(deps.storage, &address);
+    }
+    index_holder(deps.storage, &env, &address, balance)?;
+
+    let res = Response::new()
+        .add_attribute("action", "set_burn_address")
+        .add_attribute("address", address)
+        .add_attribute("burn", burn.to_string());
+    Ok(res)
+}
+
+fn update_account_freeze(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Compliance)?;
+
+    apply_account_freeze(deps, address, frozen)
+}
+
+fn apply_account_freeze(
+    deps: DepsMut,
+    address: String,
+    frozen: bool,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let action = if frozen {
+        ACCOUNT_FROZEN.save(deps.storage, &address, &true)?;
+        "freeze_account"
+    } else {
+        ACCOUNT_FROZEN.remove(deps.storage, &address);
+        "unfreeze_account"
+    };
+
+    let res = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("address", address);
+    Ok(res)
+}
+
+fn update_blacklist(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    blacklisted: bool,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Compliance)?;
+
+    apply_blacklist(deps, address, blacklisted)
+}
+
+fn apply_blacklist(
+    deps: DepsMut,
+    address: String,
+    blacklisted: bool,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    let action = if blacklisted {
+        BLACKLIST.save(deps.storage, &address, &true)?;
+        "blacklist"
+    } else {
+        BLACKLIST.remove(deps.storage, &address);
+        "remove_blacklist"
+    };
+
+    let res = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("address", address);
+    Ok(res)
+}
+
+fn set_whitelisted(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    whitelisted: bool,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Compliance)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    if whitelisted {
+        WHITELIST.save(deps.storage, &address, &true)?;
+    } else {
+        WHITELIST.remove(deps.storage, &address);
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "set_whitelisted")
+        .add_attribute("address", address)
+        .add_attribute("whitelisted", whitelisted.to_string());
+    Ok(res)
+}
+
+fn attest(
+    deps: DepsMut,
+    info: MessageInfo,
+    attestations: Vec<AttestationEntry>,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::KycProvider)?;
+    if attestations.is_empty() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut res = Response::new().add_attribute("action", "attest");
+    for entry in attestations {
+        let address = deps.api.addr_validate(&entry.address)?;
+        let attestation = Attestation {
+            level: entry.level,
+            expires: entry.expires,
+        };
+        KYC_ATTESTATIONS.save(deps.storage, &address, &attestation)?;
+        res = res.add_attribute("address", address);
+    }
+    Ok(res.add_attribute("by", info.sender))
+}
+
+fn revoke_attestation(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::KycProvider)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    KYC_ATTESTATIONS.remove(deps.storage, &address);
+
+    let res = Response::new()
+        .add_attribute("action", "revoke_attestation")
+        .add_attribute("address", address)
+        .add_attribute("by", info.sender);
+    Ok(res)
+}
+
+fn set_kyc_threshold(
+    deps: DepsMut,
+    info: MessageInfo,
+    threshold: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    match threshold {
+        Some(threshold) => KYC_THRESHOLD.save(deps.storage, &threshold)?,
+        None => KYC_THRESHOLD.remove(deps.storage),
+    }
+
+    let res = Response::new().add_attribute("action", "set_kyc_threshold");
+    Ok(res)
+}
+
+fn set_travel_rule_threshold(
+    deps: DepsMut,
+    info: MessageInfo,
+    threshold: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+
+    match threshold {
+        Some(threshold) => TRAVEL_RULE_THRESHOLD.save(deps.storage, &threshold)?,
+        None => TRAVEL_RULE_THRESHOLD.remove(deps.storage),
+    }
+
+    let res = Response::new().add_attribute("action", "set_travel_rule_threshold");
+    Ok(res)
+}
+
+fn clawback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Compliance)?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let reason = if BLACKLIST.has(deps.storage, &owner_addr) {
+        "blacklisted"
+    } else if ACCOUNT_FROZEN.has(deps.storage, &owner_addr) {
+        "frozen"
+    } else {
+        return Err(ContractError::AccountNotFrozen {});
+    };
+
+    // ensuring balance capital is not exceeded for the recovery address
+    let rcpt_addr = deps.api.addr_validate(&recipient)?;
+    ensure_cap(deps.storage, &rcpt_addr, amount)?;
+
+    // frozen balances, vesting and the policy contract are deliberately ignored, the whole
+    // balance can be seized
+    let remaining = BALANCES.update(
+        deps.storage,
+        &owner_addr,
+        |balance: Option<Uint128>| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_sub(amount)?)
+        },
+    )?;
+    BALANCES.update(
+        deps.storage,
+        &rcpt_addr,
+        |balance: Option<Uint128>| -> StdResult<_> { Ok(balance.unwrap_or_default() + amount) },
+    )?;
+    checkpoint(deps.storage, &env, &[&owner_addr, &rcpt_addr])?;
+
+    let event = Event::new("seizure")
+        .add_attribute("owner", owner_addr)
+        .add_attribute("recipient", rcpt_addr)
+        .add_attribute("amount", amount)
+        .add_attribute("remaining", remaining)
+        .add_attribute("authority", info.sender)
+        .add_attribute("reason", reason);
+    let res = Response::new()
+        .add_attribute("action", "clawback")
+        .add_event(event);
+    Ok(res)
+}
+
+fn create_vesting(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+    schedule: VestingSchedule,
+) -> Result<Response, ContractError> {
+    ensure_role(deps.as_ref(), &info.sender, Role::Admin)?;
+    validate_schedule(&schedule)?;
+
+    let address = deps.api.addr_validate(&address)?;
+    VESTING.save(deps.storage, &address, &schedule)?;
+
+    let res = Response::new()
+        .add_attribute("action", "create_vesting")
+        .add_attribute("address", address)
+        .add_attribute("amount", schedule.amount)
+        .add_attribute("start_time", schedule.start_time.to_string())
+        .add_attribute("cliff_time", schedule.cliff_time.to_string())
+        .add_attribute("end_time", schedule.end_time.to_string());
+    Ok(res)
+}
+
+/// Lets chain governance apply sensitive admin operations without holding any role.
+/// Governance has its own voting delay, so the timelock is not enforced here.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    let res = match msg {
+        SudoMsg::UpdateBalanceCap { new_cap } => apply_balance_cap(deps.storage, new_cap)?,
+        SudoMsg::SetRateLimit { limit } => apply_rate_limit(deps.storage, limit)?,
+        SudoMsg::FreezeAccount { address } => apply_account_freeze(deps, address, true)?,
+        SudoMsg::UnfreezeAccount { address } => apply_account_freeze(deps, address, false)?,
+        SudoMsg::BlackList { address } => apply_blacklist(deps, address, true)?,
+        SudoMsg::RemoveBlackList { address } => apply_blacklist(deps, address, false)?,
+        SudoMsg::GrantRole { address, role } => apply_role(deps, address, role, true)?,
+        SudoMsg::RevokeRole { address, role } => apply_role(deps, address, role, false)?,
+    };
+    Ok(res.add_attribute("by", "sudo"))
+}
+
+/// One step of the migration chain, shared by `migrate` and the migration preview
+struct MigrationStep {
+    from: &'static str,
+    to: &'static str,
+    /// fails if the step cannot run with the given message, without writing anything
+    validate: fn(Deps, &MigrateMsg) -> Result<(), ContractError>,
+    apply: fn(DepsMut, &Env, &MigrateMsg) -> Result<(), ContractError>,
+    /// namespaces the step writes, with the entries written and those that would fail
+    #[cfg(feature = "migration-preview")]
+    preview: fn(Deps) -> StdResult<Vec<NamespacePreview>>,
+}
+
+/// Every supported migration, one version at a time up to `CONTRACT_VERSION`
+const MIGRATION_STEPS: &[MigrationStep] = &[
+    MigrationStep {
+        from: "1.0.0",
+        to: "1.1.0",
+        validate: |deps, msg| migration_admin(deps, msg).map(|_| ()),
+        apply: migrate_from_v1_0_0,
+        #[cfg(feature = "migration-preview")]
+        preview: |_| Ok(vec![namespace_preview("admin", 1, vec![])]),
+    },
+    MigrationStep {
+        from: "1.1.0",
+        to: "1.2.0",
+        validate: |_, _| Ok(()),
+        apply: migrate_from_v1_1_0,
+        #[cfg(feature = "migration-preview")]
+        preview: |_| {
+            let roles = Role::ALL.len() as u64;
+            Ok(vec![namespace_preview("roles", roles, vec![])])
+        },
+    },
+    MigrationStep {
+        from: "1.2.0",
+        to: "1.3.0",
+        validate: |_, _| Ok(()),
+        apply: migrate_from_v1_2_0,
+        #[cfg(feature = "migration-preview")]
+        preview: |deps| {
+            Ok(vec![
+                namespace_preview("total_supply_snapshots", 1, vec![]),
+                preview_backfill(deps)?,
+            ])
+        },
+    },
+    MigrationStep {
+        from: "1.3.0",
+        to: "1.4.0",
+        validate: |_, _| Ok(()),
+        apply: migrate_from_v1_3_0,
+        #[cfg(feature = "migration-preview")]
+        preview: |_| Ok(vec![namespace_preview("supply_stats", 1, vec![])]),
+    },
+    MigrationStep {
+        from: "1.4.0",
+        to: "1.5.0",
+        validate: |_, _| Ok(()),
+        apply: migrate_from_v1_4_0,
+        #[cfg(feature = "migration-preview")]
+        preview: |deps| Ok(vec![preview_backfill(deps)?]),
+    },
+    MigrationStep {
+        from: "1.5.0",
+        to: "1.6.0",
+        validate: |_, _| Ok(()),
+        apply: migrate_from_v1_5_0,
+        #[cfg(feature = "migration-preview")]
+        preview: |deps| {
+            let capped = TOKEN_INFO.load(deps.storage)?.get_cap().is_some();
+            Ok(vec![
+                namespace_preview("supply_cap", u64::from(capped), vec![]),
+                namespace_preview("total_staked", 1, vec![]),
+            ])
+        },
+    },
+];
+
+fn migration_step(version: &str) -> Result<&'static MigrationStep, ContractError> {
+    MIGRATION_STEPS
+        .iter()
+        .find(|step| step.from == version)
+        .ok_or_else(|| ContractError::UnsupportedVersion {
+            version: version.to_string(),
+        })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: stored.contract,
+        });
+    }
+
+    // applying state migrations one version at a time until the current version is reached
+    let mut version = stored.version.clone();
+    while version != CONTRACT_VERSION {
+        let step = migration_step(&version)?;
+        (step.validate)(deps.as_ref(), &msg)?;
+        (step.apply)(deps.branch(), &env, &msg)?;
+        version = step.to.to_string();
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+    Ok(res)
+}
+
+/// 1.0.0 had no admin, so one has to be supplied with the migration
+fn migration_admin(deps: Deps, msg: &MigrateMsg) -> Result<Addr, ContractError> {
+    let admin = msg
+        .admin
+        .as_ref()
+        .ok_or_else(|| ContractError::AdminRequired {
+            version: String::from("1.0.0"),
+        })?;
+    Ok(deps.api.addr_validate(admin)?)
+}
+
+fn migrate_from_v1_0_0(deps: DepsMut, _env: &Env, msg: &MigrateMsg) -> Result<(), ContractError> {
+    let admin = migration_admin(deps.as_ref(), msg)?;
+    ADMIN.save(deps.storage, &admin)?;
+    Ok(())
+}
+
+/// 1.1.0 had a single admin, who is granted every role
+fn migrate_from_v1_1_0(deps: DepsMut, _env: &Env, _msg: &MigrateMsg) -> Result<(), ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    for role in Role::ALL {
+        ROLES.save(deps.storage, (&admin, role.key()), &true)?;
+    }
+    ADMIN.remove(deps.storage);
+    Ok(())
+}
+
+/// 1.2.0 kept no balance history, so it starts with the supply at the migration height.
+/// The balances, each holder voting for itself, are seeded by the `MigrateHolders` backfill,
+/// and accounts it has not reached yet cannot change meanwhile.
+fn migrate_from_v1_2_0(deps: DepsMut, env: &Env, _msg: &MigrateMsg) -> Result<(), ContractError> {
+    snapshot_balances(deps.storage, env.block.height, &[])?;
+    let backfill = Backfill {
+        seed_history: true,
+        start_after: None,
+    };
+    BACKFILL.save(deps.storage, &backfill)?;
+    Ok(())
+}
+
+/// 1.3.0 kept no supply counters, so they start from the current supply, counted as
+/// minted, and the current manually frozen balances
+fn migrate_from_v1_3_0(deps: DepsMut, _env: &Env, _msg: &MigrateMsg) -> Result<(), ContractError> {
+    let frozen = FROZEN_BALANCES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, frozen)| frozen))
+        .sum::<StdResult<Uint128>>()?;
+    let stats = SupplyStats {
+        minted: TOKEN_INFO.load(deps.storage)?.total_supply,
+        burned: Uint128::zero(),
+        frozen,
+    };
+    SUPPLY_STATS.save(deps.storage, &stats)?;
+    Ok(())
+}
+
+/// 1.4.0 had no holder index, so the `MigrateHolders` backfill builds it from the current
+/// balances, unless one is already pending from an earlier step
+fn migrate_from_v1_4_0(deps: DepsMut, _env: &Env, _msg: &MigrateMsg) -> Result<(), ContractError> {
+    if BACKFILL.may_load(deps.storage)?.is_none() {
+        let backfill = Backfill {
+            seed_history: false,
+            start_after: None,
+        };
+        BACKFILL.save(deps.storage, &backfill)?;
+    }
+    Ok(())
+}
+
+/// 1.5.0 only kept the supply cap in the cw20 minter data, so it is copied out. A cap
+/// already dropped by revoking the minter cannot be recovered. The stakes, one entry per
+/// staker, are added up once so distributions need not go through them.
+fn migrate_from_v1_5_0(deps: DepsMut, _env: &Env, _msg: &MigrateMsg) -> Result<(), ContractError> {
+    if let Some(cap) = TOKEN_INFO.load(deps.storage)?.get_cap() {
+        SUPPLY_CAP.save(deps.storage, &cap)?;
+    }
+    let staked = STAKES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, stake)| stake))
+        .sum::<StdResult<Uint128>>()?;
+    TOTAL_STAKED.save(deps.storage, &staked)?;
+    Ok(())
+}
+
+/// Goes through the next accounts of `BALANCES` after the backfill cursor. Changes made
+/// meanwhile already went through `checkpoint`, so the current balances stay accurate.
+fn migrate_holders(deps: DepsMut, env: Env, limit: Option<u32>) -> Result<Response, ContractError> {
+    let mut backfill = BACKFILL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoBackfillPending {})?;
+    let limit = limit
+        .unwrap_or(DEFAULT_BACKFILL_LIMIT)
+        .min(MAX_BACKFILL_LIMIT) as usize;
+    let start = backfill
+        .start_after
+        .as_ref()
+        .map(|address| Bound::exclusive(address.as_bytes()));
+    let balances = BALANCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, balance) = item?;
+            Ok((Addr::unchecked(String::from_utf8(key)?), balance))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    if backfill.seed_history {
+        let accounts: Vec<_> = balances.iter().map(|(address, _)| address).collect();
+        snapshot_balances(deps.storage, env.block.height, &accounts)?;
+    }
+    for (address, balance) in &balances {
+        index_holder(deps.storage, &env, address, *balance)?;
+    }
+
+    let complete = balances.len() < limit;
+    if complete {
+        BACKFILL.remove(deps.storage);
+    } else if let Some((address, _)) = balances.last() {
+        backfill.start_after = Some(address.clone());
+        BACKFILL.save(deps.storage, &backfill)?;
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "migrate_holders")
+        .add_attribute("accounts", balances.len().to_string())
+        .add_attribute("complete", complete.to_string());
+    Ok(res)
+}
+
+#[cfg(feature = "migration-preview")]
+pub fn query_migration_preview(
+    deps: Deps,
+    to_version: String,
+    msg: MigrateMsg,
+) -> StdResult<MigrationPreviewResponse> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(StdError::generic_err(format!(
+            "Cannot migrate from different contract type: {}",
+            stored.contract
+        )));
+    }
+    if to_version != CONTRACT_VERSION {
+        return Err(StdError::generic_err(format!(
+            "Can only migrate to {}",
+            CONTRACT_VERSION
+        )));
+    }
+
+    // walking the same steps as migrate, running their validation without writing anything
+    let mut namespaces: Vec<NamespacePreview> = vec![];
+    let mut version = stored.version.clone();
+    while version != CONTRACT_VERSION {
+        let step =
+            migration_step(&version).map_err(|err| StdError::generic_err(err.to_string()))?;
+        (step.validate)(deps, &msg).map_err(|err| StdError::generic_err(err.to_string()))?;
+        for preview in (step.preview)(deps)? {
+            // a namespace written by several steps, like the backfill, is written once
+            if !namespaces
+                .iter()
+                .any(|namespace| namespace.namespace == preview.namespace)
+            {
+                namespaces.push(preview);
+            }
+        }
+        version = step.to.to_string();
+    }
+
+    Ok(MigrationPreviewResponse {
+        from_version: stored.version,
+        to_version,
+        namespaces,
+    })
+}
+
+#[cfg(feature = "migration-preview")]
+fn namespace_preview(
+    namespace: &str,
+    affected_entries: u64,
+    invalid_entries: Vec<String>,
+) -> NamespacePreview {
+    NamespacePreview {
+        namespace: namespace.to_string(),
+        affected_entries,
+        invalid_entries,
+    }
+}
+
+/// Every balance goes through the `MigrateHolders` backfill, which cannot get past a key
+/// that is not an address. Those are reported hex encoded.
+#[cfg(feature = "migration-preview")]
+fn preview_backfill(deps: Deps) -> StdResult<NamespacePreview> {
+    let mut affected_entries = 0;
+    let mut invalid_entries = vec![];
+    for key in BALANCES.keys(deps.storage, None, None, Order::Ascending) {
+        affected_entries += 1;
+        if std::str::from_utf8(&key).is_err() {
+            invalid_entries.push(hex::encode(&key));
+        }
+    }
+    Ok(namespace_preview(
+        "backfill",
+        affected_entries,
+        invalid_entries,
+    ))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: Query) -> StdResult<Binary> {
+    match msg {
+        // inherited from cw20-base
+        Query::TokenInfo {} => to_binary(&query_token_info(deps)?),
+        Query::Balance { address } => to_binary(&query_balance(deps, address)?),
+        Query::FrozenBalance { address } => to_binary(&query_frozen_balance(deps, address)?),
+        Query::Allowance { owner, spender } => to_binary(&query_allowance(deps, owner, spender)?),
+        Query::Minter {} => to_binary(&query_minter(deps)?),
+        Query::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_all_allowances(deps, owner, start_after, limit)?),
+        Query::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        Query::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        Query::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+        Query::SpendableBalance { address } => {
+            to_binary(&query_spendable_balance(deps, env, address)?)
+        }
+        Query::AllFrozenBalances { start_after, limit } => {
+            to_binary(&query_all_frozen_balances(deps, start_after, limit)?)
+        }
+        Query::TopHolders { start_after, limit } => {
+            to_binary(&query_top_holders(deps, start_after, limit)?)
+        }
+        Query::Router { router } => to_binary(&query_router(deps, router)?),
+        Query::RouterOptIn { owner, router } => {
+            to_binary(&query_router_opt_in(deps, owner, router)?)
+        }
+        Query::Capabilities {} => to_binary(&query_capabilities(deps)?),
+        #[cfg(feature = "migration-preview")]
+        Query::MigrationPreview { to_version, msg } => {
+            to_binary(&query_migration_preview(deps, to_version, msg)?)
+        }
+        Query::CirculatingSupply {} => to_binary(&query_circulating_supply(deps)?),
+        Query::SupplyBreakdown {} => to_binary(&query_supply_breakdown(deps, env)?),
+        Query::IsFrozen { address } => to_binary(&query_is_frozen(deps, address)?),
+        Query::IsBlacklisted { address } => to_binary(&query_is_blacklisted(deps, address)?),
+        Query::IsWhitelisted { address } => to_binary(&query_is_whitelisted(deps, address)?),
+        Query::TransferPolicy {} => to_binary(&query_transfer_policy(deps)?),
+        Query::TokenHooks {} => to_binary(&query_token_hooks(deps)?),
+        Query::PolicyContract {} => to_binary(&query_policy_contract(deps)?),
+        Query::AccountStats { address } => to_binary(&query_account_stats(deps, address)?),
+        Query::TransferMemos { start_after, limit } => {
+            to_binary(&query_transfer_memos(deps, start_after, limit)?)
+        }
+        Query::Attestation { address } => to_binary(&query_attestation(deps, env, address)?),
+        Query::RiskSnapshot { address } => to_binary(&query_risk_snapshot(deps, env, address)?),
+        Query::SelfLock { address } => to_binary(&query_self_lock(deps, env, address)?),
+        Query::PermitNonce { owner } => to_binary(&query_permit_nonce(deps, owner)?),
+        Query::AuthorizationState { authorizer, nonce } => {
+            to_binary(&query_authorization_state(deps, authorizer, nonce)?)
+        }
+        Query::BalanceAt { address, height } => {
+            to_binary(&query_balance_at(deps, address, height)?)
+        }
+        Query::BurnStats {} => to_binary(&query_burn_stats(deps)?),
+        Query::Minters { start_after, limit } => {
+            to_binary(&query_minters(deps, start_after, limit)?)
+        }
+        Query::TransferFee {
+            sender,
+            recipient,
+            amount,
+        } => to_binary(&query_transfer_fee(deps, sender, recipient, amount)?),
+        Query::AirdropStage { stage } => to_binary(&query_airdrop_stage(deps, stage)?),
+        Query::IsClaimed { stage, address } => to_binary(&query_is_claimed(deps, stage, address)?),
+        Query::Staked { address } => to_binary(&query_staked(deps, address)?),
+        Query::Claims { address } => to_binary(&query_claims(deps, address)?),
+        Query::PendingRewards { address } => to_binary(&query_pending_rewards(deps, address)?),
+        Query::Delegation { address } => to_binary(&query_delegation(deps, address)?),
+        Query::VotingPower { address } => to_binary(&query_voting_power(deps, address)?),
+        Query::VotingPowerAt { address, height } => {
+            to_binary(&query_voting_power_at(deps, address, height)?)
+        }
+        Query::TotalSupplyAt { height } => to_binary(&query_total_supply_at(deps, height)?),
+        Query::Roles { address } => to_binary(&query_roles(deps, address)?),
+        Query::PendingActions { start_after, limit } => {
+            to_binary(&query_pending_actions(deps, start_after, limit)?)
+        }
+        Query::FrozenSchedule { address } => to_binary(&query_frozen_schedule(deps, env, address)?),
+        Query::VestingInfo { address } => to_binary(&query_vesting_info(deps, env, address)?),
+    }
+}
+
+pub fn query_frozen_balance(deps: Deps, address: String) -> StdResult<FrozenBalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
     let balance = FROZEN_BALANCES
         .may_load(deps.storage, &address)?
         .unwrap_or_default();
-    Ok(BalanceResponse { balance })
+    Ok(FrozenBalanceResponse { balance })
+}
+
+pub fn query_circulating_supply(deps: Deps) -> StdResult<CirculatingSupplyResponse> {
+    let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let burned = BURN_ADDRESSES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|key| {
+            let address = Addr::unchecked(String::from_utf8(key)?);
+            Ok(BALANCES
+                .may_load(deps.storage, &address)?
+                .unwrap_or_default())
+        })
+        .sum::<StdResult<Uint128>>()?;
+
+    Ok(CirculatingSupplyResponse {
+        total_supply,
+        burned,
+        circulating_supply: total_supply.checked_sub(burned)?,
+    })
+}
+
+pub fn query_supply_breakdown(deps: Deps, env: Env) -> StdResult<SupplyBreakdownResponse> {
+    let total_supply = TOKEN_INFO.load(deps.storage)?.total_supply;
+    let escrowed = BALANCES
+        .may_load(deps.storage, &env.contract.address)?
+        .unwrap_or_default();
+    let stats = SUPPLY_STATS.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(SupplyBreakdownResponse {
+        total_supply,
+        circulating_supply: total_supply.saturating_sub(escrowed + stats.frozen),
+        escrowed,
+        frozen: stats.frozen,
+        total_minted: stats.minted,
+        total_burned: stats.burned,
+    })
+}
+
+pub fn query_spendable_balance(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<SpendableBalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let frozen = frozen_amount(deps.storage, &env, &address)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let bal_cap = BALANCE_CAP.load(deps.storage)?;
+
+    Ok(SpendableBalanceResponse {
+        balance,
+        frozen,
+        spendable: balance.saturating_sub(frozen),
+        cap_headroom: bal_cap.saturating_sub(balance),
+    })
+}
+
+pub fn query_all_frozen_balances(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllFrozenBalancesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let frozen_balances = FROZEN_BALANCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, amount) = item?;
+            Ok(Cw20Coin {
+                address: String::from_utf8(key)?,
+                amount,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(AllFrozenBalancesResponse { frozen_balances })
+}
+
+pub fn query_top_holders(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<TopHoldersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // index keys are (balance, address), so the holder's current balance locates it
+    let end = match start_after {
+        Some(address) => {
+            let address = deps.api.addr_validate(&address)?;
+            let balance = holders()
+                .may_load(deps.storage, &address)?
+                .unwrap_or_default();
+            let key = (U128Key::from(balance.u128()), address.as_bytes().to_vec());
+            Some(Bound::Exclusive(key.joined_key()))
+        }
+        None => None,
+    };
+
+    let holders = holders()
+        .idx
+        .balance
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|item| {
+            let (key, amount) = item?;
+            Ok(Cw20Coin {
+                address: String::from_utf8(key)?,
+                amount,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(TopHoldersResponse {
+        holders,
+        complete: BACKFILL.may_load(deps.storage)?.is_none(),
+    })
+}
+
+pub fn query_router(deps: Deps, router: String) -> StdResult<RouterResponse> {
+    let router = deps.api.addr_validate(&router)?;
+    let config = ROUTERS.load(deps.storage, &router)?;
+    Ok(RouterResponse {
+        max_per_tx: config.max_per_tx,
+        expiry: config.expiry,
+    })
+}
+
+pub fn query_router_opt_in(
+    deps: Deps,
+    owner: String,
+    router: String,
+) -> StdResult<RouterOptInResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let router = deps.api.addr_validate(&router)?;
+    let allowance = ROUTER_ALLOWANCES
+        .may_load(deps.storage, (&owner, &router))?
+        .unwrap_or_default();
+    Ok(RouterOptInResponse {
+        opted_in: !allowance.is_zero(),
+        allowance,
+    })
+}
+
+pub fn query_frozen_schedule(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<FrozenScheduleResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let tranches = FROZEN_TRANCHES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(FrozenScheduleResponse {
+        tranches: active_tranches(tranches, env.block.time.seconds()),
+    })
+}
+
+pub fn query_vesting_info(deps: Deps, env: Env, address: String) -> StdResult<VestingInfoResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let schedule = VESTING.may_load(deps.storage, &address)?;
+    let (vested, unvested) = match &schedule {
+        Some(schedule) => {
+            let unvested = unvested_amount(schedule, env.block.time.seconds());
+            (schedule.amount - unvested, unvested)
+        }
+        None => (Uint128::zero(), Uint128::zero()),
+    };
+    Ok(VestingInfoResponse {
+        schedule,
+        vested,
+        unvested,
+    })
+}
+
+pub fn query_is_frozen(deps: Deps, address: String) -> StdResult<IsFrozenResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(IsFrozenResponse {
+        frozen: ACCOUNT_FROZEN.has(deps.storage, &address),
+    })
+}
+
+pub fn query_is_blacklisted(deps: Deps, address: String) -> StdResult<IsBlacklistedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(IsBlacklistedResponse {
+        blacklisted: BLACKLIST.has(deps.storage, &address),
+    })
+}
+
+pub fn query_is_whitelisted(deps: Deps, address: String) -> StdResult<IsWhitelistedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(IsWhitelistedResponse {
+        whitelisted: WHITELIST.has(deps.storage, &address),
+    })
+}
+
+pub fn query_attestation(deps: Deps, env: Env, address: String) -> StdResult<AttestationResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let attestation = KYC_ATTESTATIONS.may_load(deps.storage, &address)?;
+    Ok(AttestationResponse {
+        valid: attestation
+            .as_ref()
+            .is_some_and(|attestation| env.block.time.seconds() < attestation.expires),
+        attestation,
+        kyc_threshold: KYC_THRESHOLD.may_load(deps.storage)?,
+    })
+}
+
+pub fn query_transfer_memos(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TransferMemosResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(U64Key::from(id)));
+
+    let memos = TRANSFER_MEMOS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, memo)| memo))
+        .collect::<StdResult<_>>()?;
+
+    Ok(TransferMemosResponse {
+        threshold: TRAVEL_RULE_THRESHOLD.may_load(deps.storage)?,
+        memos,
+    })
+}
+
+pub fn query_account_stats(deps: Deps, address: String) -> StdResult<AccountStatsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let stats = ACCOUNT_STATS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(AccountStatsResponse {
+        tx_count: stats.tx_count,
+        total_sent: stats.total_sent,
+        total_received: stats.total_received,
+        last_activity_height: stats.last_activity_height,
+    })
+}
+
+pub fn query_token_hooks(deps: Deps) -> StdResult<TokenHooksResponse> {
+    Ok(TokenHooksResponse {
+        hooks: TOKEN_HOOKS.query_hooks(deps)?.hooks,
+    })
+}
+
+pub fn query_policy_contract(deps: Deps) -> StdResult<PolicyContractResponse> {
+    let policy = POLICY_CONTRACT.may_load(deps.storage)?;
+    Ok(PolicyContractResponse {
+        fail_open: policy.as_ref().is_some_and(|policy| policy.fail_open),
+        address: policy.map(|policy| policy.address.into()),
+    })
+}
+
+pub fn query_transfer_policy(deps: Deps) -> StdResult<TransferPolicyResponse> {
+    Ok(TransferPolicyResponse {
+        policy: TRANSFER_POLICY.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_pending_actions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingActionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|id| Bound::exclusive(U64Key::from(id)));
+
+    let actions = PENDING_ACTIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, pending)| pending))
+        .collect::<StdResult<_>>()?;
+
+    Ok(PendingActionsResponse {
+        delay: TIMELOCK_DELAY.may_load(deps.storage)?.unwrap_or_default(),
+        actions,
+    })
+}
+
+pub fn query_risk_snapshot(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<RiskSnapshotResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let frozen = frozen_amount(deps.storage, &env, &address)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let bal_cap = BALANCE_CAP.load(deps.storage)?;
+
+    // volume of an elapsed window no longer counts
+    let window = RATE_LIMITS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let outbound_volume = if env.block.time.seconds() < window.window_start + RATE_LIMIT_WINDOW {
+        window.consumed
+    } else {
+        Uint128::zero()
+    };
+
+    Ok(RiskSnapshotResponse {
+        balance,
+        frozen,
+        cap_headroom: bal_cap.saturating_sub(balance),
+        outbound_volume,
+        rate_limit: RATE_LIMIT.may_load(deps.storage)?,
+        rate_limit_exempt: RATE_LIMIT_EXEMPTIONS.has(deps.storage, &address),
+        account_frozen: ACCOUNT_FROZEN.has(deps.storage, &address),
+        blacklisted: BLACKLIST.has(deps.storage, &address),
+        burn_address: BURN_ADDRESSES.has(deps.storage, &address),
+    })
+}
+
+pub fn query_self_lock(deps: Deps, env: Env, address: String) -> StdResult<SelfLockResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let locked_until = SELF_LOCKS
+        .may_load(deps.storage, &address)?
+        .filter(|until| env.block.time.seconds() < *until);
+    let recovery_address = RECOVERY_ADDRESSES
+        .may_load(deps.storage, &address)?
+        .map(String::from);
+    Ok(SelfLockResponse {
+        locked_until,
+        recovery_address,
+    })
+}
+
+pub fn query_permit_nonce(deps: Deps, owner: String) -> StdResult<PermitNonceResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    Ok(PermitNonceResponse {
+        nonce: PERMIT_NONCES
+            .may_load(deps.storage, &owner)?
+            .unwrap_or_default(),
+    })
+}
+
+pub fn query_authorization_state(
+    deps: Deps,
+    authorizer: String,
+    nonce: Binary,
+) -> StdResult<AuthorizationStateResponse> {
+    let authorizer = deps.api.addr_validate(&authorizer)?;
+    Ok(AuthorizationStateResponse {
+        used: USED_AUTHORIZATIONS.has(deps.storage, (&authorizer, nonce.as_slice())),
+    })
+}
+
+pub fn query_balance_at(deps: Deps, address: String, height: u64) -> StdResult<BalanceAtResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCE_SNAPSHOTS
+        .may_load_at_height(deps.storage, &address, height)?
+        .unwrap_or_default();
+    Ok(BalanceAtResponse { balance, height })
+}
+
+pub fn query_total_supply_at(deps: Deps, height: u64) -> StdResult<TotalSupplyAtResponse> {
+    let total_supply = TOTAL_SUPPLY_SNAPSHOTS
+        .may_load_at_height(deps.storage, TOTAL_SUPPLY_KEY, height)?
+        .unwrap_or_default();
+    Ok(TotalSupplyAtResponse { total_supply })
+}
+
+pub fn query_minters(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<MintersResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let minters = MINTERS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (minter, allowance) = item?;
+            Ok(MinterInfo {
+                minter: String::from_utf8(minter)?,
+                cap: allowance.cap,
+                minted: allowance.minted,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(MintersResponse { minters })
+}
+
+pub fn query_transfer_fee(
+    deps: Deps,
+    sender: String,
+    recipient: String,
+    amount: Uint128,
+) -> StdResult<TransferFeeResponse> {
+    let sender = deps.api.addr_validate(&sender)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let charges = transfer_charges(deps.storage, &sender, &recipient, amount)?;
+    Ok(TransferFeeResponse {
+        fee: charges.fee,
+        burned: charges.burned,
+        amount_received: amount - charges.total(),
+    })
+}
+
+pub fn query_burn_stats(deps: Deps) -> StdResult<BurnStatsResponse> {
+    Ok(BurnStatsResponse {
+        burn_rate_bps: BURN_RATE.may_load(deps.storage)?.unwrap_or_default(),
+        total_burned: TAX_BURNED.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_airdrop_stage(deps: Deps, stage: u8) -> StdResult<AirdropStageResponse> {
+    let airdrop = AIRDROP_STAGES.load(deps.storage, U8Key::from(stage))?;
+    Ok(AirdropStageResponse {
+        stage,
+        merkle_root: airdrop.merkle_root,
+        total_amount: airdrop.total_amount,
+        claimed_amount: airdrop.claimed_amount,
+    })
+}
+
+pub fn query_is_claimed(deps: Deps, stage: u8, address: String) -> StdResult<IsClaimedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(IsClaimedResponse {
+        claimed: AIRDROP_CLAIMS.has(deps.storage, (&address, U8Key::from(stage))),
+    })
+}
+
+pub fn query_staked(deps: Deps, address: String) -> StdResult<StakedResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let rate_limit = match RATE_LIMIT.may_load(deps.storage)? {
+        Some(limit) => Some(staked_limit(deps.storage, &address, limit)?),
+        None => None,
+    };
+    Ok(StakedResponse {
+        stake: STAKES.may_load(deps.storage, &address)?.unwrap_or_default(),
+        rate_limit,
+    })
+}
+
+pub fn query_claims(deps: Deps, address: String) -> StdResult<ClaimsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    CLAIMS.query_claims(deps, &address)
+}
+
+pub fn query_pending_rewards(deps: Deps, address: String) -> StdResult<PendingRewardsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = BALANCES
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let earning = reward_weight(deps.storage, &address, balance)?;
+    Ok(PendingRewardsResponse {
+        denom: REWARD_DENOM.may_load(deps.storage)?,
+        amount: pending_rewards(deps.storage, &address, earning)?.pending,
+    })
+}
+
+pub fn query_delegation(deps: Deps, address: String) -> StdResult<DelegationResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(DelegationResponse {
+        delegate: delegate_of(deps.storage, &address)?,
+    })
+}
+
+pub fn query_voting_power(deps: Deps, address: String) -> StdResult<VotingPowerResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let power = VOTING_POWER
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    Ok(VotingPowerResponse { power })
+}
+
+pub fn query_voting_power_at(
+    deps: Deps,
+    address: String,
+    height: u64,
+) -> StdResult<VotingPowerResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let power = VOTING_POWER
+        .may_load_at_height(deps.storage, &address, height)?
+        .unwrap_or_default();
+    Ok(VotingPowerResponse { power })
+}
+
+pub fn query_roles(deps: Deps, address: String) -> StdResult<RolesResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let roles = Role::ALL
+        .iter()
+        .filter(|role| ROLES.has(deps.storage, (&address, role.key())))
+        .copied()
+        .collect();
+    Ok(RolesResponse { roles })
+}
+
+pub fn query_capabilities(deps: Deps) -> StdResult<CapabilitiesResponse> {
+    let mut extensions: Vec<String> = CAPABILITIES.iter().map(|ext| ext.to_string()).collect();
+    // someone can mint, either the cw20 minter or a registered one, and the supply cap
+    // leaves room for it
+    let token_info = TOKEN_INFO.load(deps.storage)?;
+    let minter = token_info.mint.is_some()
+        || MINTERS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+    let room = SUPPLY_CAP
+        .may_load(deps.storage)?
+        .is_none_or(|cap| token_info.total_supply < cap);
+    if minter && room {
+        extensions.push(String::from("mintable"));
+    }
+    Ok(CapabilitiesResponse { extensions })
 }
@@ -1,4 +1,5 @@
 use cosmwasm_std::{StdError, Uint128};
+use cw_controllers::HookError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,6 +7,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Hook(#[from] HookError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -38,6 +42,135 @@ pub enum ContractError {
 
     #[error("Balance is frozen")]
     BalanceFrozen {},
+
+    #[error("Account is frozen")]
+    AccountFrozen {},
+
+    #[error("Action is timelocked and has to be scheduled")]
+    TimelockRequired {},
+
+    #[error("Scheduled action cannot be executed before {eta}")]
+    TimelockNotReady { eta: u64 },
+
+    #[error("Account is locked until {until}")]
+    AccountLocked { until: u64 },
+
+    #[error("Account lock can only be extended")]
+    CannotShortenLock {},
+
+    #[error("Public key does not belong to the signer")]
+    InvalidPubkey {},
+
+    #[error("Invalid signature")]
+    InvalidSignature {},
+
+    #[error("Invalid nonce, expected {expected}")]
+    InvalidNonce { expected: u64 },
+
+    #[error("Authorization is not valid before {valid_after}")]
+    AuthorizationNotYetValid { valid_after: u64 },
+
+    #[error("Authorization already used")]
+    AuthorizationUsed {},
+
+    #[error("Sent funds do not match the reward amount")]
+    RewardFundsMismatch {},
+
+    #[error("Reward denom is not set")]
+    RewardDenomNotSet {},
+
+    #[error("Reward denom cannot change once rewards were distributed")]
+    RewardsAlreadyDistributed {},
+
+    #[error("Invalid merkle root, expected 32 hex encoded bytes")]
+    InvalidMerkleRoot {},
+
+    #[error("Invalid merkle proof")]
+    InvalidProof {},
+
+    #[error("Airdrop already claimed")]
+    AirdropClaimed {},
+
+    #[error("Airdrop allocation exhausted")]
+    AirdropExhausted {},
+
+    #[error("Transfer fee and burn rate cannot exceed 10000 basis points together")]
+    InvalidTransferFee {},
+
+    #[error("Minting would exceed the minter's cap")]
+    MinterCapExceeded {},
+
+    #[error("Account {address} is not whitelisted")]
+    NotWhitelisted { address: String },
+
+    #[error("Account {address} has no valid KYC attestation")]
+    AttestationRequired { address: String },
+
+    #[error("Transfers above {threshold} require a memo hash")]
+    MemoRequired { threshold: Uint128 },
+
+    #[error("Memo hash must be 32 hex encoded bytes")]
+    InvalidMemoHash {},
+
+    #[error("Denied by the policy contract: {reason}")]
+    PolicyDenied { reason: String },
+
+    #[error("Policy contract is unreachable")]
+    PolicyUnreachable {},
+
+    #[error("Account is blacklisted")]
+    Blacklisted {},
+
+    #[error("Clawback is only allowed from frozen or blacklisted accounts")]
+    AccountNotFrozen {},
+
+    #[error("Vesting schedule must satisfy start_time <= cliff_time <= end_time")]
+    InvalidVestingSchedule {},
+
+    #[error("Rate limit exceeded, retry after {retry_after}")]
+    RateLimitExceeded { retry_after: u64 },
+
+    #[error("Amount exceeds the router limit")]
+    RouterLimitExceeded {},
+
+    #[error("Amount exceeds what the owner lets the router move")]
+    RouterAllowanceExceeded {},
+
+    #[error("Name is not in the expected format (3-50 UTF-8 bytes)")]
+    InvalidName {},
+
+    #[error("Ticker symbol is not in expected format [a-zA-Z\\-]{{3,12}}")]
+    InvalidSymbol {},
+
+    #[error("Decimals must not exceed 18")]
+    InvalidDecimals {},
+
+    #[error("Initial supply greater than cap")]
+    InitialSupplyExceedsCap {},
+
+    #[error("Admin is required to migrate from {version}")]
+    AdminRequired { version: String },
+
+    #[error("Logo binary data exceeds 5KB limit")]
+    LogoTooBig {},
+
+    #[error("Invalid xml preamble for SVG")]
+    InvalidXmlPreamble {},
+
+    #[error("Invalid png header")]
+    InvalidPngHeader {},
+
+    #[error("Cannot migrate from different contract type: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from unsupported version: {version}")]
+    UnsupportedVersion { version: String },
+
+    #[error("Balance history of {address} is not migrated yet")]
+    MigrationPending { address: String },
+
+    #[error("No migration backfill is pending")]
+    NoBackfillPending {},
 }
 
 impl From<cw20_base::ContractError> for ContractError {
@@ -52,12 +185,9 @@ impl From<cw20_base::ContractError> for ContractError {
             cw20_base::ContractError::Expired {} => ContractError::Expired {},
             cw20_base::ContractError::NoAllowance {} => ContractError::NoAllowance {},
             cw20_base::ContractError::CannotExceedCap {} => ContractError::CannotExceedCap {},
-            // This should never happen, as this contract doesn't use logo
-            cw20_base::ContractError::LogoTooBig {}
-            | cw20_base::ContractError::InvalidPngHeader {}
-            | cw20_base::ContractError::InvalidXmlPreamble {} => {
-                ContractError::Std(StdError::generic_err(err.to_string()))
-            }
+            cw20_base::ContractError::LogoTooBig {} => ContractError::LogoTooBig {},
+            cw20_base::ContractError::InvalidPngHeader {} => ContractError::InvalidPngHeader {},
+            cw20_base::ContractError::InvalidXmlPreamble {} => ContractError::InvalidXmlPreamble {},
         }
     }
 }
@@ -2,11 +2,20 @@
 extern crate arrayref;
 
 mod error;
+mod logic;
+mod merkle;
 pub mod state;
 
 pub mod contract;
 pub mod msg;
 #[cfg(test)]
+mod multitest;
+mod operations;
+pub mod responses;
+mod signature;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+#[cfg(test)]
 mod tests;
 
 pub use crate::{error::*, state::*};
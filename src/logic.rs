@@ -0,0 +1,418 @@
+//! Pure balance, cap, compliance and rate limit rules shared by the contract handlers and
+//! the off-chain simulation. Nothing here touches storage: the contract loads the values
+//! from storage and the simulation from its in-memory ledger.
+
+use crate::{
+    error::ContractError,
+    state::{FrozenTranche, RateLimitWindow, TransferPolicy, VestingSchedule},
+    RATE_LIMIT_WINDOW,
+};
+use cosmwasm_std::{Decimal, StdError, Uint128};
+use cw20::{EmbeddedLogo, Logo};
+
+/// Largest embedded logo accepted, matching the cw20-base upload limit
+const LOGO_SIZE_CAP: usize = 5 * 1024;
+
+/// Contract wide settings the transfer checks apply
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransferRules {
+    pub policy: TransferPolicy,
+    /// amount above which both parties need a valid KYC attestation
+    pub kyc_threshold: Option<Uint128>,
+    pub bal_cap: Uint128,
+}
+
+/// What the transfer checks know of an account taking part in a transfer. The whitelist
+/// and attestation may be left out when the `TransferRules` do not consult them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Party {
+    pub address: String,
+    pub balance: Uint128,
+    pub blacklisted: bool,
+    /// registered burn addresses are not capped
+    pub burn_address: bool,
+    pub whitelisted: bool,
+    /// block time (in seconds) at which its KYC attestation expires, if it has one
+    pub attested_until: Option<u64>,
+}
+
+/// What the transfer checks know of the account tokens move out of
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Payer {
+    pub party: Party,
+    /// manually frozen balance, locked tranches and unvested amount together
+    pub frozen: Uint128,
+    /// frozen outright by compliance
+    pub account_frozen: bool,
+    /// block time (in seconds) until which the account locked itself, if it did
+    pub locked_until: Option<u64>,
+}
+
+/// Amounts taken out of a transfer before it reaches the recipient
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TransferCharges {
+    /// paid to the fee collector
+    pub fee: Uint128,
+    /// destroyed by the burn tax
+    pub burned: Uint128,
+}
+
+impl TransferCharges {
+    pub fn total(&self) -> Uint128 {
+        self.fee + self.burned
+    }
+}
+
+/// Runs every check a transfer of `amount` from `payer` to `recipient` goes through at
+/// time `now` (in seconds) before any token moves, in the order the contract applies them.
+/// `collector` is the fee collector, needed whenever `charges` include a fee.
+pub fn check_transfer(
+    rules: &TransferRules,
+    payer: &Payer,
+    recipient: &Party,
+    collector: Option<&Party>,
+    amount: Uint128,
+    charges: TransferCharges,
+    now: u64,
+) -> Result<(), ContractError> {
+    ensure_spendable(payer, amount, now)?;
+    ensure_transfer_allowed(rules.policy, Some(&payer.party), recipient)?;
+    ensure_attested(rules.kyc_threshold, &[&payer.party, recipient], amount, now)?;
+
+    let net = amount
+        .checked_sub(charges.total())
+        .map_err(StdError::from)?;
+    ensure_cap(recipient, net, rules.bal_cap)?;
+    match collector {
+        Some(collector) => ensure_collector_cap(collector, recipient, net, charges, rules.bal_cap),
+        None => Ok(()),
+    }
+}
+
+/// Runs every check a mint of `amount` to `recipient` goes through before any token moves
+pub fn check_mint(
+    rules: &TransferRules,
+    recipient: &Party,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    ensure_transfer_allowed(rules.policy, None, recipient)?;
+    ensure_cap(recipient, amount, rules.bal_cap)
+}
+
+/// Fails if `payer` is blacklisted, frozen outright, self locked at time `now` (in seconds)
+/// or if moving `amount` out of it would dip into its frozen balance
+pub fn ensure_spendable(payer: &Payer, amount: Uint128, now: u64) -> Result<(), ContractError> {
+    if payer.party.blacklisted {
+        return Err(ContractError::Blacklisted {});
+    }
+    if payer.account_frozen {
+        return Err(ContractError::AccountFrozen {});
+    }
+    if let Some(until) = payer.locked_until {
+        if now < until {
+            return Err(ContractError::AccountLocked { until });
+        }
+    }
+    ensure_unfrozen(payer.party.balance, payer.frozen, amount)
+}
+
+/// Fails if `policy` does not let `sender` move tokens to `recipient`. Mints have no
+/// sender and are only held to the recipient rule.
+pub fn ensure_transfer_allowed(
+    policy: TransferPolicy,
+    sender: Option<&Party>,
+    recipient: &Party,
+) -> Result<(), ContractError> {
+    let sender = match policy {
+        TransferPolicy::Open => return Ok(()),
+        TransferPolicy::WhitelistedOnly => sender,
+        TransferPolicy::WhitelistedRecipients => None,
+    };
+    for party in sender.into_iter().chain(Some(recipient)) {
+        if !party.whitelisted {
+            return Err(ContractError::NotWhitelisted {
+                address: party.address.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Fails if `amount` is above the KYC `threshold` and any of `parties` has no attestation
+/// valid at time `now` (in seconds)
+pub fn ensure_attested(
+    threshold: Option<Uint128>,
+    parties: &[&Party],
+    amount: Uint128,
+    now: u64,
+) -> Result<(), ContractError> {
+    match threshold {
+        Some(threshold) if amount > threshold => {}
+        _ => return Ok(()),
+    }
+    for party in parties {
+        if party.attested_until.is_none_or(|expires| now >= expires) {
+            return Err(ContractError::AttestationRequired {
+                address: party.address.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Fails if moving `amount` without a memo hash is above the travel rule `threshold`
+pub fn ensure_memo_not_required(
+    threshold: Option<Uint128>,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    match threshold {
+        Some(threshold) if amount > threshold => Err(ContractError::MemoRequired { threshold }),
+        _ => Ok(()),
+    }
+}
+
+/// Fails if `recipient` is blacklisted or if receiving `amount` would push it over
+/// `bal_cap`. Registered burn addresses are not capped.
+pub fn ensure_cap(
+    recipient: &Party,
+    amount: Uint128,
+    bal_cap: Uint128,
+) -> Result<(), ContractError> {
+    if recipient.blacklisted {
+        return Err(ContractError::Blacklisted {});
+    }
+    if recipient.burn_address {
+        return Ok(());
+    }
+    ensure_within_cap(recipient.balance, amount, bal_cap)
+}
+
+/// Fails if crediting the fee would push `collector` over `bal_cap`, counting the `net`
+/// amount of the transfer as well when the collector is also the `recipient`
+pub fn ensure_collector_cap(
+    collector: &Party,
+    recipient: &Party,
+    net: Uint128,
+    charges: TransferCharges,
+    bal_cap: Uint128,
+) -> Result<(), ContractError> {
+    if charges.fee.is_zero() {
+        return Ok(());
+    }
+    let credited = match collector.address == recipient.address {
+        true => charges.fee.checked_add(net).map_err(StdError::from)?,
+        false => charges.fee,
+    };
+    ensure_cap(collector, credited, bal_cap)
+}
+
+/// Returns the part of `balance` that is not `frozen`, failing if more is frozen than held
+pub fn spendable(balance: Uint128, frozen: Uint128) -> Result<Uint128, ContractError> {
+    balance
+        .checked_sub(frozen)
+        .map_err(|_| ContractError::BalanceFrozen {})
+}
+
+/// Fails if moving `amount` out of `balance` would dip into the `frozen` part of it
+pub fn ensure_unfrozen(
+    balance: Uint128,
+    frozen: Uint128,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if spendable(balance, frozen)? < amount {
+        return Err(ContractError::BalanceFrozen {});
+    }
+    Ok(())
+}
+
+/// Returns the tranches that are still locked at time `now` (in seconds)
+pub fn active_tranches(tranches: Vec<FrozenTranche>, now: u64) -> Vec<FrozenTranche> {
+    tranches
+        .into_iter()
+        .filter(|tranche| tranche.unlock_at > now)
+        .collect()
+}
+
+/// Returns the manually `frozen` amount plus what `tranches` still lock and `vesting` still
+/// holds back at time `now` (in seconds)
+pub fn frozen_total(
+    frozen: Uint128,
+    tranches: &[FrozenTranche],
+    vesting: Option<&VestingSchedule>,
+    now: u64,
+) -> Result<Uint128, ContractError> {
+    let locked = locked_amount(tranches, now)?;
+    let unvested = match vesting {
+        Some(schedule) => unvested_amount(schedule, now),
+        None => Uint128::zero(),
+    };
+    Ok(frozen
+        .checked_add(locked)
+        .and_then(|frozen| frozen.checked_add(unvested))
+        .map_err(StdError::from)?)
+}
+
+/// Returns the total amount still locked by `tranches` at time `now` (in seconds)
+pub fn locked_amount(tranches: &[FrozenTranche], now: u64) -> Result<Uint128, ContractError> {
+    tranches
+        .iter()
+        .filter(|tranche| tranche.unlock_at > now)
+        .try_fold(Uint128::zero(), |total, tranche| {
+            total.checked_add(tranche.amount).map_err(StdError::from)
+        })
+        .map_err(ContractError::from)
+}
+
+/// Fails unless `start_time <= cliff_time <= end_time`
+pub fn validate_schedule(schedule: &VestingSchedule) -> Result<(), ContractError> {
+    if schedule.start_time > schedule.cliff_time || schedule.cliff_time > schedule.end_time {
+        return Err(ContractError::InvalidVestingSchedule {});
+    }
+    Ok(())
+}
+
+/// Returns the part of `schedule` that is still unvested at time `now` (in seconds)
+pub fn unvested_amount(schedule: &VestingSchedule, now: u64) -> Uint128 {
+    if now < schedule.cliff_time {
+        return schedule.amount;
+    }
+    if now >= schedule.end_time {
+        return Uint128::zero();
+    }
+
+    let vested = schedule.amount.multiply_ratio(
+        now - schedule.start_time,
+        schedule.end_time - schedule.start_time,
+    );
+    schedule.amount - vested
+}
+
+/// Fails if receiving `amount` on top of `balance` would go over `bal_cap`
+pub fn ensure_within_cap(
+    balance: Uint128,
+    amount: Uint128,
+    bal_cap: Uint128,
+) -> Result<(), ContractError> {
+    match balance.checked_add(amount) {
+        Ok(total) if total <= bal_cap => Ok(()),
+        _ => Err(ContractError::CannotExceedCap {}),
+    }
+}
+
+/// Returns the total supply after minting `amount` on top of `total_supply`, failing if
+/// it would go over the mint `cap`
+pub fn ensure_mint_cap(
+    total_supply: Uint128,
+    amount: Uint128,
+    cap: Option<Uint128>,
+) -> Result<Uint128, ContractError> {
+    let total_supply = total_supply.checked_add(amount).map_err(StdError::from)?;
+    if cap.is_some_and(|cap| total_supply > cap) {
+        return Err(ContractError::CannotExceedCap {});
+    }
+    Ok(total_supply)
+}
+
+/// Returns the rate limit window of an address after moving `amount` out at time `now`
+/// (in seconds) under the configured `limit`, or nothing to record if it is `exempt`. The
+/// limit is raised by `boost` per `staked` token.
+#[allow(clippy::too_many_arguments)]
+pub fn consume_rate_limit(
+    window: RateLimitWindow,
+    limit: Uint128,
+    exempt: bool,
+    staked: Uint128,
+    boost: Decimal,
+    now: u64,
+    amount: Uint128,
+) -> Result<Option<RateLimitWindow>, ContractError> {
+    if exempt {
+        return Ok(None);
+    }
+    let limit = boosted_limit(limit, staked, boost);
+    consume_window(window, limit, now, amount).map(Some)
+}
+
+/// Returns the rate limit window after moving `amount` out at time `now` (in seconds),
+/// starting a fresh window once the previous one has elapsed
+pub fn consume_window(
+    window: RateLimitWindow,
+    limit: Uint128,
+    now: u64,
+    amount: Uint128,
+) -> Result<RateLimitWindow, ContractError> {
+    let mut window = if now >= window.window_start + RATE_LIMIT_WINDOW {
+        RateLimitWindow {
+            window_start: now,
+            consumed: Uint128::zero(),
+        }
+    } else {
+        window
+    };
+
+    window.consumed = match window.consumed.checked_add(amount) {
+        Ok(consumed) if consumed <= limit => consumed,
+        _ => {
+            return Err(ContractError::RateLimitExceeded {
+                retry_after: window.window_start + RATE_LIMIT_WINDOW,
+            })
+        }
+    };
+    Ok(window)
+}
+
+/// Returns the rate limit of an address with `staked` tokens, raised by `boost` per token
+pub fn boosted_limit(limit: Uint128, staked: Uint128, boost: Decimal) -> Uint128 {
+    limit.saturating_add(staked * boost)
+}
+
+/// Returns the fee of `bps` basis points charged on a transfer of `amount`
+pub fn fee_amount(amount: Uint128, bps: u16) -> Uint128 {
+    amount.multiply_ratio(bps, 10_000u128)
+}
+
+/// Returns the fee of `fee_bps` (when a transfer fee is set) and the burn tax of
+/// `burn_rate` basis points charged on a transfer of `amount`, nothing when `exempt`
+pub fn transfer_charges(
+    exempt: bool,
+    fee_bps: Option<u16>,
+    burn_rate: u16,
+    amount: Uint128,
+) -> TransferCharges {
+    if exempt {
+        return TransferCharges::default();
+    }
+    TransferCharges {
+        fee: fee_bps.map_or_else(Uint128::zero, |bps| fee_amount(amount, bps)),
+        burned: fee_amount(amount, burn_rate),
+    }
+}
+
+/// Fails unless `logo` passes the same checks cw20-base applies on upload
+pub fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
+    const PNG_HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+    match logo {
+        Logo::Url(_) => Ok(()),
+        Logo::Embedded(EmbeddedLogo::Svg(data)) => {
+            // the first tag has to be an xml preamble
+            let preamble = data.split_inclusive(|c| *c == b'>').next().unwrap_or(&[]);
+            if !(preamble.starts_with(b"<?xml ") && preamble.ends_with(b"?>")) {
+                Err(ContractError::InvalidXmlPreamble {})
+            } else if data.len() > LOGO_SIZE_CAP {
+                Err(ContractError::LogoTooBig {})
+            } else {
+                Ok(())
+            }
+        }
+        Logo::Embedded(EmbeddedLogo::Png(data)) => {
+            if data.len() > LOGO_SIZE_CAP {
+                Err(ContractError::LogoTooBig {})
+            } else if !data.starts_with(&PNG_HEADER) {
+                Err(ContractError::InvalidPngHeader {})
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Verification of merkle proofs for airdrop claims
+
+use crate::error::ContractError;
+use sha2::{Digest, Sha256};
+
+/// Fails unless `proof` links the leaf of `address` receiving `amount` to `merkle_root`.
+/// Leaves are the sha256 of the address followed by the amount, and each pair of nodes is
+/// hashed in ascending order, so proofs carry no left/right flags.
+pub fn verify_proof(
+    merkle_root: &str,
+    address: &str,
+    amount: &str,
+    proof: &[String],
+) -> Result<(), ContractError> {
+    let mut hash: [u8; 32] = Sha256::digest(format!("{}{}", address, amount).as_bytes()).into();
+    for node in proof {
+        let mut sibling = [0u8; 32];
+        hex::decode_to_slice(node, &mut sibling).map_err(|_| ContractError::InvalidProof {})?;
+
+        let (first, second) = if hash < sibling {
+            (hash, sibling)
+        } else {
+            (sibling, hash)
+        };
+        hash = Sha256::new().chain(first).chain(second).finalize().into();
+    }
+
+    let mut root = [0u8; 32];
+    hex::decode_to_slice(merkle_root, &mut root)
+        .map_err(|_| ContractError::InvalidMerkleRoot {})?;
+    if hash != root {
+        return Err(ContractError::InvalidProof {});
+    }
+    Ok(())
+}
@@ -1,11 +1,14 @@
 use super::*;
-use cosmwasm_std::{Binary, StdError, StdResult, Uint128};
+pub use crate::responses::*;
+use cosmwasm_std::{to_binary, Binary, CosmosMsg, Decimal, StdResult, Uint128, WasmMsg};
 use cw0::Expiration;
 use cw20::{Cw20Coin, Logo, MinterResponse};
+use cw20_base::msg::InstantiateMarketingInfo;
 pub use cw_controllers::ClaimsResponse;
 use schemars::JsonSchema;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub struct Instantiate {
     /// name of the derivative token
     pub name: String,
@@ -22,6 +25,25 @@ pub struct Instantiate {
     /// balance cap for each token holder (eg. balance cap for each user = 1000,
     /// users can only hold up to 1000 tokens.
     pub bal_cap: Uint128,
+    /// vesting schedules locking part of the initial balances
+    #[serde(default)]
+    pub vesting: Vec<VestingAccount>,
+    /// project, description, marketing address and logo shown by cw20 frontends
+    #[serde(default)]
+    pub marketing: Option<InstantiateMarketingInfo>,
+    /// addresses whitelisted for restricted transfer policies from the start
+    #[serde(default)]
+    pub initial_whitelist: Vec<String>,
+    /// burn/sink addresses registered from the start
+    #[serde(default)]
+    pub initial_burnlist: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct VestingAccount {
+    pub address: String,
+    pub schedule: VestingSchedule,
 }
 
 impl Instantiate {
@@ -29,20 +51,16 @@ impl Instantiate {
         self.mint.as_ref().and_then(|v| v.cap)
     }
 
-    pub fn validate(&self) -> StdResult<()> {
+    pub fn validate(&self) -> Result<(), ContractError> {
         // Check name, symbol, decimals
         if !is_valid_name(&self.name) {
-            return Err(StdError::generic_err(
-                "Name is not in the expected format (3-50 UTF-8 bytes)",
-            ));
+            return Err(ContractError::InvalidName {});
         }
         if !is_valid_symbol(&self.symbol) {
-            return Err(StdError::generic_err(
-                "Ticker symbol is not in expected format [a-zA-Z\\-]{3,12}",
-            ));
+            return Err(ContractError::InvalidSymbol {});
         }
         if self.decimals > 18 {
-            return Err(StdError::generic_err("Decimals must not exceed 18"));
+            return Err(ContractError::InvalidDecimals {});
         }
         Ok(())
     }
@@ -71,12 +89,39 @@ fn is_valid_symbol(symbol: &str) -> bool {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub enum Execute {
     /// Transfer is a base message to move tokens to another account without triggering actions
     Transfer {
         recipient: String,
         amount: Uint128,
     },
+    /// Transfer carrying the hex encoded sha256 hash of the off-chain travel rule payload,
+    /// recorded on-chain. Required above the travel rule threshold.
+    TransferWithMemo {
+        recipient: String,
+        amount: Uint128,
+        memo_hash: String,
+    },
+    /// Transfers to many recipients at once, applying the same checks as Transfer to each.
+    /// Either all transfers succeed or none does.
+    TransferBatch {
+        transfers: Vec<Cw20Coin>,
+    },
+    /// Moves amount tokens from -> to, authorized by from's secp256k1 signature over the
+    /// matching `TransferAuthorization`, so a relayer can pay the fees. Valid while block time
+    /// (in seconds) is after valid_after and before valid_before; every nonce works once.
+    TransferWithAuthorization {
+        from: String,
+        to: String,
+        amount: Uint128,
+        valid_after: u64,
+        valid_before: u64,
+        nonce: Binary,
+        /// compressed secp256k1 public key of from
+        pubkey: Binary,
+        signature: Binary,
+    },
     /// Burn is a base message to destroy tokens forever
     Burn {
         amount: Uint128,
@@ -104,6 +149,19 @@ pub enum Execute {
         amount: Uint128,
         expires: Option<Expiration>,
     },
+    /// Only with "approval" extension. Sets the allowance of spender over owner's tokens to
+    /// amount until expires, authorized by owner's secp256k1 signature over the matching
+    /// `PermitPayload`. Can be submitted by anyone.
+    PermitAllowance {
+        owner: String,
+        spender: String,
+        amount: Uint128,
+        nonce: u64,
+        expires: Expiration,
+        /// compressed secp256k1 public key of owner
+        pubkey: Binary,
+        signature: Binary,
+    },
     /// Only with "approval" extension. Transfers amount tokens from owner -> recipient
     /// if `env.sender` has sufficient pre-approval.
     TransferFrom {
@@ -119,6 +177,14 @@ pub enum Execute {
         amount: Uint128,
         msg: Binary,
     },
+    /// Only with "approval" extension. Applies `permit` like `PermitAllowance`, then sends
+    /// the permitted amount from its owner to contract with call as the message, like
+    /// `SendFrom`. Only the permit's spender can submit it.
+    PermitAndCall {
+        permit: Permit,
+        call: Binary,
+        contract: String,
+    },
     // Only with "approval" extension. Destroys tokens forever
     BurnFrom {
         owner: String,
@@ -130,6 +196,62 @@ pub enum Execute {
         recipient: String,
         amount: Uint128,
     },
+    /// Only with the "mintable" extension. If authorized, mints to every recipient in one
+    /// message, enforcing both the mint cap and the per-address balance cap.
+    MintBatch {
+        recipients: Vec<Cw20Coin>,
+    },
+    /// Only with admin role or the current minter. Hands the mint authority to new_minter,
+    /// keeping the supply cap, or revokes it for good when None. Revoking leaves the
+    /// supply cap in force.
+    UpdateMinter {
+        new_minter: Option<String>,
+    },
+    /// Only with admin role. Allows address to mint up to cap tokens in total, on top of
+    /// the minter set at instantiation. The supply cap still applies, with or without
+    /// a cw20 minter.
+    AddMinter {
+        address: String,
+        cap: Option<Uint128>,
+    },
+    /// Only with admin role. Withdraws the mint rights granted with AddMinter
+    RemoveMinter {
+        address: String,
+    },
+    /// Only with admin role. Changes the cumulative cap of a registered minter, counting
+    /// what it already minted
+    SetMinterCap {
+        address: String,
+        cap: Option<Uint128>,
+    },
+    /// Only with admin role. Registers a DEX router that can move up to `max_per_tx`
+    /// tokens per transaction on behalf of any holder who opted in, until `expiry`.
+    AuthorizeRouter {
+        router: String,
+        max_per_tx: Uint128,
+        expiry: Expiration,
+    },
+    /// Only with admin role. Removes a registered router
+    RevokeRouter {
+        router: String,
+    },
+    /// Lets a registered router move up to amount of the sender's tokens in total,
+    /// replacing what it could still move before. Each router transfer uses it up.
+    OptInRouter {
+        router: String,
+        amount: Uint128,
+    },
+    /// Withdraws the sender's opt-in for a router
+    OptOutRouter {
+        router: String,
+    },
+    /// Only for registered routers that are neither blacklisted nor frozen. Transfers
+    /// amount tokens from an opted-in owner to recipient, out of what the owner opted in for.
+    RouterTransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
     /// Only with the "marketing" extension. If authorized, updates marketing metadata.
     /// Setting None/null for any of these will leave it unchanged.
     /// Setting Some("") will clear this field on the contract storage
@@ -143,24 +265,276 @@ pub enum Execute {
     },
     /// If set as the "marketing" role on the contract, upload a new URL, SVG, or PNG for the token
     UploadLogo(Logo),
-    /// Update frozen list
+    /// Only with compliance role. Update frozen list
     UpdateFrozenList(UpdateType),
+    /// Only with admin role. Updates the balance cap for each token holder
+    UpdateBalanceCap {
+        new_cap: Uint128,
+    },
+    /// Only with admin role. Sets the delay sensitive admin actions have to wait once
+    /// scheduled. Has to be scheduled itself while a delay is set.
+    SetTimelockDelay {
+        delay: u64,
+    },
+    /// Only with admin role. Queues `action` for execution once the timelock delay elapsed
+    Schedule {
+        action: ScheduledAction,
+    },
+    /// Only with admin role. Applies a scheduled action whose delay elapsed
+    ExecuteScheduled {
+        id: u64,
+    },
+    /// Only with admin role. Drops a scheduled action
+    CancelScheduled {
+        id: u64,
+    },
+    /// Only with admin role. Sets the maximum amount an address can move out within
+    /// a 24h window. Setting None/null removes the limit.
+    SetRateLimit {
+        limit: Option<Uint128>,
+    },
+    /// Only with admin role. Restricts who may send and receive tokens. Has to be
+    /// scheduled while a timelock delay is set.
+    SetTransferPolicy {
+        policy: TransferPolicy,
+    },
+    /// Only with compliance role. Adds or removes an account from the whitelist consulted
+    /// by restrictive transfer policies
+    SetWhitelisted {
+        address: String,
+        whitelisted: bool,
+    },
+    /// Only with KYC provider role. Records a KYC attestation of `level` for an account,
+    /// valid until `expires` (block time in seconds), replacing any previous one
+    Attest {
+        address: String,
+        level: u8,
+        expires: u64,
+    },
+    /// Only with KYC provider role. Records several attestations at once
+    AttestBatch {
+        attestations: Vec<AttestationEntry>,
+    },
+    /// Only with KYC provider role. Drops the attestation of an account
+    RevokeAttestation {
+        address: String,
+    },
+    /// Only with admin role. Requires both parties of transfers above `threshold` to hold
+    /// a valid attestation. Setting None/null lifts the requirement.
+    SetKycThreshold {
+        threshold: Option<Uint128>,
+    },
+    /// Only with admin role. Requires transfers above `threshold` to go through
+    /// TransferWithMemo. Setting None/null lifts the requirement.
+    SetTravelRuleThreshold {
+        threshold: Option<Uint128>,
+    },
+    /// Only with admin role. Exempts or re-includes an address from the rate limit
+    SetRateLimitExemption {
+        address: String,
+        exempt: bool,
+    },
+    /// Only with admin role. Registers or unregisters a burn/sink address
+    SetBurnAddress {
+        address: String,
+        burn: bool,
+    },
+    /// Only with compliance role. Blocks every outgoing transfer, send and burn of an account
+    FreezeAccount {
+        address: String,
+    },
+    /// Only with compliance role. Lifts an account freeze
+    UnfreezeAccount {
+        address: String,
+    },
+    /// Only with admin role. Grants `role` to `address`
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    /// Only with admin role. Revokes `role` from `address`
+    RevokeRole {
+        address: String,
+        role: Role,
+    },
+    /// Locks the sender's account until block time `until` (in seconds), rejecting every
+    /// outgoing transfer meanwhile. An existing lock can only be extended.
+    LockSelf {
+        until: u64,
+    },
+    /// Only with the recovery address of `owner`. Lifts its self lock early
+    UnlockSelf {
+        owner: String,
+    },
+    /// Only with fee manager role. Charges bps basis points of every transfer and send to
+    /// the sender, paid to collector. Setting 0 bps removes the fee. Has to be scheduled
+    /// while a timelock delay is set.
+    SetTransferFee {
+        bps: u16,
+        collector: String,
+    },
+    /// Only with fee manager role. Exempts or re-includes an address from the transfer fee
+    /// and burn tax, for transfers it sends as well as receives
+    SetFeeExemption {
+        address: String,
+        exempt: bool,
+    },
+    /// Only with fee manager role. Burns bps basis points of every transfer and send out of
+    /// the sender's balance, reducing the total supply. Setting 0 bps removes the burn tax.
+    /// Has to be scheduled while a timelock delay is set.
+    SetBurnRate {
+        bps: u16,
+    },
+    /// Only with admin role. Opens a new airdrop stage whose claims mint up to total_amount
+    /// tokens to the addresses listed under the hex encoded merkle_root
+    RegisterMerkleRoot {
+        merkle_root: String,
+        total_amount: Uint128,
+    },
+    /// Mints the sender's airdrop of the given stage, proven by the hex encoded sibling
+    /// hashes linking its (address, amount) leaf to the stage's merkle root
+    ClaimAirdrop {
+        stage: u8,
+        amount: Uint128,
+        proof: Vec<String>,
+    },
+    /// Moves amount tokens from the sender's balance into its stake. Staked tokens raise
+    /// the sender's rate limit.
+    Stake {
+        amount: Uint128,
+    },
+    /// Takes amount tokens out of the sender's stake, claimable with ClaimUnstaked once the
+    /// unbonding period has passed
+    Unstake {
+        amount: Uint128,
+    },
+    /// Returns the sender's unstaked tokens whose unbonding period has passed
+    ClaimUnstaked {},
+    /// Only with admin role. Sets the unbonding period (in seconds) and the rate limit
+    /// added per staked token.
+    UpdateStakingConfig {
+        unbonding_period: u64,
+        rate_limit_boost: Decimal,
+    },
+    /// Only with admin role. Sets the native denom rewards are paid in. Cannot change once
+    /// rewards were distributed.
+    SetRewardDenom {
+        denom: String,
+    },
+    /// Distributes amount of the reward denom, sent along with the message, to all holders
+    /// pro-rata to their balances. Holders claim their share with ClaimRewards.
+    DistributeRewards {
+        amount: Uint128,
+    },
+    /// Pays out the rewards accumulated by the sender
+    ClaimRewards {},
+    /// Hands the voting power of the sender's balance, now and after any later change, to
+    /// delegatee. Delegating to oneself takes it back.
+    Delegate {
+        delegatee: String,
+    },
+    /// Sets or clears the address allowed to lift the sender's self lock early.
+    /// Cannot be changed while the account is locked.
+    SetRecoveryAddress {
+        address: Option<String>,
+    },
+    /// Registers the sender to receive a `TransferHook` message whenever it is the
+    /// recipient of a transfer
+    RegisterTransferHook {},
+    /// Stops sending `TransferHook` messages to the sender
+    UnregisterTransferHook {},
+    /// Only with admin role. Registers a contract notified with a `TokenHookMsg` of every
+    /// transfer, send, mint and burn. Sends are reported once the receiving contract
+    /// accepted them. A failing hook does not block the token movement.
+    AddTokenHook {
+        address: String,
+    },
+    /// Only with admin role. Sets the contract queried with a `PolicyQueryMsg` before every
+    /// transfer, send, mint and burn, or removes it with None/null. With fail_open, actions
+    /// go through while the policy contract cannot be queried, otherwise they fail.
+    SetPolicyContract {
+        address: Option<String>,
+        fail_open: bool,
+    },
+    /// Only with admin role. Stops notifying a token hook
+    RemoveTokenHook {
+        address: String,
+    },
+    /// Only with compliance role. Blocks an account from both sending and receiving tokens
+    BlackList {
+        address: String,
+    },
+    /// Only with compliance role. Removes an account from the blacklist
+    RemoveBlackList {
+        address: String,
+    },
+    /// Only with compliance role. Seizes `amount` from a frozen or blacklisted account
+    /// into `recipient`, bypassing allowances
+    Clawback {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Only with admin role. Locks part of an account's balance behind a vesting
+    /// schedule, replacing any previous schedule of that account
+    CreateVesting {
+        address: String,
+        schedule: VestingSchedule,
+    },
+    /// Goes through up to `limit` more accounts of the backfill left by a migration,
+    /// adding them to the holder index and seeding their balance history if needed.
+    /// Anyone can call it until the backfill is done.
+    MigrateHolders {
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub enum UpdateType {
     Add(Cw20Coin),
     Sub(Cw20Coin),
     Discard(String),
+    /// Freezes `amount` until block time `unlock_at` (in seconds), after which it becomes
+    /// spendable again without another update
+    Lock {
+        address: String,
+        amount: Uint128,
+        unlock_at: u64,
+    },
+}
+
+/// Sensitive admin operations chain governance can apply through `sudo`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub enum SudoMsg {
+    UpdateBalanceCap { new_cap: Uint128 },
+    SetRateLimit { limit: Option<Uint128> },
+    FreezeAccount { address: String },
+    UnfreezeAccount { address: String },
+    BlackList { address: String },
+    RemoveBlackList { address: String },
+    GrantRole { address: String, role: Role },
+    RevokeRole { address: String, role: Role },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct MigrateMsg {
+    /// admin to record when migrating from a version without admin rights
+    pub admin: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
 pub enum Query {
     /// Implements CW20. Returns the current balance of the given address, 0 if unset.
     Balance { address: String },
-    /// Implements CW20. Returns the current frozen balance of the given address, 0 if unset.
+    /// Returns the current frozen balance of the given address, 0 if unset.
+    /// Return type: FrozenBalanceResponse.
     FrozenBalance { address: String },
     /// Implements CW20. Returns metadata on the contract - name, decimals, supply, etc.
     TokenInfo {},
@@ -171,4 +545,317 @@ pub enum Query {
     /// Implements CW20 "allowance" extension.
     /// Returns how much spender can use from owner account, 0 if unset.
     Allowance { owner: String, spender: String },
+    /// Only with "enumerable" extension (and "allowances")
+    /// Returns all allowances this owner has approved. Supports pagination.
+    /// Return type: AllAllowancesResponse.
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with "enumerable" extension
+    /// Returns all accounts that have balances. Supports pagination.
+    /// Return type: AllAccountsResponse.
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Only with "marketing" extension
+    /// Returns more metadata on the contract to display in the client:
+    /// description, logo, project url, etc.
+    /// Return type: MarketingInfoResponse.
+    MarketingInfo {},
+    /// Only with "marketing" extension
+    /// Downloads the embedded logo data (if stored on chain). Errors if no logo data is stored for this
+    /// contract.
+    /// Return type: DownloadLogoResponse.
+    DownloadLogo {},
+    /// Returns balance, frozen amount, spendable amount and remaining headroom under the
+    /// balance cap for the given address.
+    /// Return type: SpendableBalanceResponse.
+    SpendableBalance { address: String },
+    /// Returns all accounts with a frozen balance. Supports pagination.
+    /// Return type: AllFrozenBalancesResponse.
+    AllFrozenBalances {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the holders with the largest balances, largest first. Supports pagination,
+    /// starting after the given holder. Return type: TopHoldersResponse.
+    TopHolders {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the limits of a registered router.
+    /// Return type: RouterResponse.
+    Router { router: String },
+    /// Returns whether owner has opted in to router and what router can still move.
+    /// Return type: RouterOptInResponse.
+    RouterOptIn { owner: String, router: String },
+    /// Returns the extensions supported by this contract, for runtime feature detection.
+    /// Return type: CapabilitiesResponse.
+    Capabilities {},
+    /// Reports which storage namespaces migrating the stored contract to `to_version`
+    /// with `msg` would rewrite and which of their entries would fail, computed read-only.
+    /// Fails like the migration would when a step rejects `msg`.
+    /// Return type: MigrationPreviewResponse.
+    #[cfg(feature = "migration-preview")]
+    MigrationPreview { to_version: String, msg: MigrateMsg },
+    /// Returns total supply minus balances held by registered burn addresses.
+    /// Return type: CirculatingSupplyResponse.
+    CirculatingSupply {},
+    /// Splits the total supply into circulating, escrowed and frozen amounts, along with
+    /// the totals ever minted and burned. Return type: SupplyBreakdownResponse.
+    SupplyBreakdown {},
+    /// Returns the balance of the given address at the beginning of block height, before
+    /// any transaction of that block. Return type: BalanceAtResponse.
+    BalanceAt { address: String, height: u64 },
+    /// Returns the total supply at the beginning of block height.
+    /// Return type: TotalSupplyAtResponse.
+    TotalSupplyAt { height: u64 },
+    /// Returns the address voting with the balance of the given address.
+    /// Return type: DelegationResponse.
+    Delegation { address: String },
+    /// Returns the current voting power of the given address.
+    /// Return type: VotingPowerResponse.
+    VotingPower { address: String },
+    /// Returns the voting power of the given address at the beginning of block height.
+    /// Return type: VotingPowerResponse.
+    VotingPowerAt { address: String, height: u64 },
+    /// Returns the minters registered with AddMinter and how much each minted.
+    /// Return type: MintersResponse.
+    Minters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the fee and burn tax sender would be charged to move amount to recipient.
+    /// Return type: TransferFeeResponse.
+    TransferFee {
+        sender: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Returns the burn rate and the total burned by it so far.
+    /// Return type: BurnStatsResponse.
+    BurnStats {},
+    /// Returns the merkle root and allocation of the given airdrop stage.
+    /// Return type: AirdropStageResponse.
+    AirdropStage { stage: u8 },
+    /// Returns whether the given address claimed its airdrop of the given stage.
+    /// Return type: IsClaimedResponse.
+    IsClaimed { stage: u8, address: String },
+    /// Returns the tokens staked by the given address and its effective rate limit.
+    /// Return type: StakedResponse.
+    Staked { address: String },
+    /// Returns the unstaked tokens of the given address that are unbonding or claimable.
+    /// Return type: ClaimsResponse.
+    Claims { address: String },
+    /// Returns the rewards the given address can claim.
+    /// Return type: PendingRewardsResponse.
+    PendingRewards { address: String },
+    /// Returns whether the given account is frozen outright.
+    /// Return type: IsFrozenResponse.
+    IsFrozen { address: String },
+    /// Returns whether the given account is blacklisted.
+    /// Return type: IsBlacklistedResponse.
+    IsBlacklisted { address: String },
+    /// Returns whether the given account is whitelisted.
+    /// Return type: IsWhitelistedResponse.
+    IsWhitelisted { address: String },
+    /// Returns the KYC attestation of the given account and whether it is still valid.
+    /// Return type: AttestationResponse.
+    Attestation { address: String },
+    /// Returns the travel rule threshold and the recorded transfer memos.
+    /// Supports pagination.
+    /// Return type: TransferMemosResponse.
+    TransferMemos {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns how often the given account's balance changed, the totals sent and
+    /// received and the height of its last activity.
+    /// Return type: AccountStatsResponse.
+    AccountStats { address: String },
+    /// Returns the policy contract consulted before token movements, if any.
+    /// Return type: PolicyContractResponse.
+    PolicyContract {},
+    /// Returns the contracts registered as token hooks.
+    /// Return type: TokenHooksResponse.
+    TokenHooks {},
+    /// Returns who may send and receive tokens.
+    /// Return type: TransferPolicyResponse.
+    TransferPolicy {},
+    /// Returns the timelock delay and the scheduled admin actions with their ETA.
+    /// Supports pagination.
+    /// Return type: PendingActionsResponse.
+    PendingActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Bundles everything a transaction screening service needs to know about an address:
+    /// balances, outbound volume within the current rate limit window and list memberships.
+    /// Return type: RiskSnapshotResponse.
+    RiskSnapshot { address: String },
+    /// Returns the self lock and recovery address of the given address.
+    /// Return type: SelfLockResponse.
+    SelfLock { address: String },
+    /// Returns the nonce the next permit signed by owner has to carry.
+    /// Return type: PermitNonceResponse.
+    PermitNonce { owner: String },
+    /// Returns whether authorizer already used the transfer authorization nonce.
+    /// Return type: AuthorizationStateResponse.
+    AuthorizationState { authorizer: String, nonce: Binary },
+    /// Returns the roles granted to the given address.
+    /// Return type: RolesResponse.
+    Roles { address: String },
+    /// Returns the time-locked frozen tranches of the given address that have not expired yet.
+    /// Return type: FrozenScheduleResponse.
+    FrozenSchedule { address: String },
+    /// Returns the vesting schedule of the given address with its vested and unvested
+    /// amounts at the current block time.
+    /// Return type: VestingInfoResponse.
+    VestingInfo { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct AttestationEntry {
+    pub address: String,
+    pub level: u8,
+    pub expires: u64,
+}
+
+/// Sent to accounts registered through `Execute::RegisterTransferHook` whenever they
+/// receive a transfer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TransferHookMsg {
+    pub sender: String,
+    pub amount: Uint128,
+}
+
+impl TransferHookMsg {
+    /// serializes the message, wrapped as `{"transfer_hook": {...}}`
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = TransferHookExecuteMsg::TransferHook(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum TransferHookExecuteMsg {
+    TransferHook(TransferHookMsg),
+}
+
+/// Sent to contracts registered through `Execute::AddTokenHook` after every token movement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenHookMsg {
+    Transfer {
+        sender: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    Mint {
+        recipient: String,
+        amount: Uint128,
+    },
+    Burn {
+        owner: String,
+        amount: Uint128,
+    },
+}
+
+impl TokenHookMsg {
+    /// serializes the message, wrapped as `{"token_hook": {...}}`
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = TokenHookExecuteMsg::TokenHook(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum TokenHookExecuteMsg {
+    TokenHook(TokenHookMsg),
+}
+
+/// Query sent to the policy contract set through `Execute::SetPolicyContract`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyQueryMsg {
+    /// Return type: PolicyResponse.
+    CheckPolicy { action: TokenHookMsg },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PolicyResponse {
+    pub allowed: bool,
+    /// why the action is denied
+    pub reason: Option<String>,
+}
+
+/// Payload owners sign, JSON encoded, to authorize `Execute::PermitAllowance`.
+/// The chain id and contract address prevent replaying it elsewhere.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PermitPayload {
+    pub chain_id: String,
+    pub contract: String,
+    pub owner: String,
+    pub spender: String,
+    pub amount: Uint128,
+    pub nonce: u64,
+    pub expires: Expiration,
+}
+
+/// A signed `PermitPayload`, as submitted with `Execute::PermitAndCall`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct Permit {
+    pub owner: String,
+    pub spender: String,
+    pub amount: Uint128,
+    pub nonce: u64,
+    pub expires: Expiration,
+    /// compressed secp256k1 public key of owner
+    pub pubkey: Binary,
+    pub signature: Binary,
+}
+
+/// Payload signers sign, JSON encoded, to authorize `Execute::TransferWithAuthorization`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferAuthorization {
+    pub chain_id: String,
+    pub contract: String,
+    pub from: String,
+    pub to: String,
+    pub amount: Uint128,
+    pub valid_after: u64,
+    pub valid_before: u64,
+    pub nonce: Binary,
 }
@@ -0,0 +1,452 @@
+use crate::{
+    contract::{execute, instantiate, migrate, query, reply, sudo},
+    error::ContractError,
+    msg::*,
+    state::{SUPPLY_CAP, TOTAL_STAKED},
+};
+use anyhow::Result as AnyResult;
+use cosmwasm_std::{
+    testing::{mock_env, MockApi, MockStorage},
+    to_binary, Addr, Api, Binary, CanonicalAddr, Deps, DepsMut, Empty, Env, Event, MessageInfo,
+    RecoverPubkeyError, Response, StdError, StdResult, Uint128, VerificationError,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ReceiveMsg, MinterResponse};
+use cw_multi_test::{App, AppResponse, BankKeeper, Contract, ContractWrapper, Executor};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const OWNER: &str = "addr0001";
+const ADMIN: &str = "creator";
+const MINTER: &str = "minter";
+// the mock handler denies every action moving more than this
+const POLICY_LIMIT: u128 = 3000;
+
+/// `MockApi`, except that it takes addresses as they are: the addresses multi-test gives
+/// contracts, like "Contract #0", are not normalized
+struct AppApi(MockApi);
+
+impl Api for AppApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.addr_canonicalize(human)?;
+        Ok(Addr::unchecked(human))
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        if human.is_empty() {
+            return Err(StdError::generic_err("Invalid input: empty address"));
+        }
+        Ok(CanonicalAddr::from(human.as_bytes()))
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        Ok(Addr::unchecked(String::from_utf8(canonical.to_vec())?))
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.0
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.0.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.0
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.0.debug(message)
+    }
+}
+
+fn mock_app() -> App {
+    let env = mock_env();
+    App::new(
+        AppApi(MockApi::default()),
+        env.block,
+        BankKeeper::new(),
+        MockStorage::new(),
+    )
+}
+
+fn token_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(execute, instantiate, query)
+        .with_reply(reply)
+        .with_sudo(sudo)
+        .with_migrate(migrate);
+    Box::new(contract)
+}
+
+fn token_instantiate_msg() -> Instantiate {
+    Instantiate {
+        name: "Bash Shell".to_string(),
+        symbol: "BASH".to_string(),
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: OWNER.to_string(),
+            amount: Uint128::new(10000),
+        }],
+        mint: Some(MinterResponse {
+            minter: MINTER.to_string(),
+            cap: Some(Uint128::new(20000)),
+        }),
+        frozen_balances: vec![],
+        bal_cap: Uint128::new(100000),
+        vesting: vec![],
+        marketing: None,
+        initial_whitelist: vec![],
+        initial_burnlist: vec![],
+    }
+}
+
+// stands in for a 1.5.0 deployment: same storage, minus what 1.6.0 added
+fn legacy_instantiate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Instantiate,
+) -> Result<Response, ContractError> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    SUPPLY_CAP.remove(deps.storage);
+    TOTAL_STAKED.remove(deps.storage);
+    cw2::set_contract_version(deps.storage, "token_contract", "1.5.0")?;
+    Ok(res)
+}
+
+fn legacy_token_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(execute, legacy_instantiate, query).with_reply(reply);
+    Box::new(contract)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum ReceiverMsg {
+    Receive(Cw20ReceiveMsg),
+}
+
+const RECEIVED: Item<Vec<Cw20ReceiveMsg>> = Item::new("received");
+
+// accepts every send except the ones asking it to fail
+fn receiver_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: ReceiverMsg,
+) -> StdResult<Response> {
+    let ReceiverMsg::Receive(msg) = msg;
+    if msg.msg == Binary::from(b"fail") {
+        return Err(StdError::generic_err("receive rejected"));
+    }
+    let mut received = RECEIVED.may_load(deps.storage)?.unwrap_or_default();
+    received.push(msg);
+    RECEIVED.save(deps.storage, &received)?;
+    Ok(Response::new())
+}
+
+fn receiver_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn receiver_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&RECEIVED.may_load(deps.storage)?.unwrap_or_default())
+}
+
+fn receiver_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(receiver_execute, receiver_instantiate, receiver_query);
+    Box::new(contract)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum HandlerMsg {
+    TokenHook(TokenHookMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum HandlerQuery {
+    CheckPolicy { action: TokenHookMsg },
+    Notified {},
+}
+
+const NOTIFIED: Item<Vec<TokenHookMsg>> = Item::new("notified");
+
+// records the token hooks it is sent and answers the token's policy queries
+fn handler_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: HandlerMsg,
+) -> StdResult<Response> {
+    let HandlerMsg::TokenHook(msg) = msg;
+    let mut notified = NOTIFIED.may_load(deps.storage)?.unwrap_or_default();
+    notified.push(msg);
+    NOTIFIED.save(deps.storage, &notified)?;
+    Ok(Response::new())
+}
+
+fn handler_query(deps: Deps, _env: Env, msg: HandlerQuery) -> StdResult<Binary> {
+    match msg {
+        HandlerQuery::CheckPolicy { action } => {
+            let amount = match action {
+                TokenHookMsg::Transfer { amount, .. }
+                | TokenHookMsg::Mint { amount, .. }
+                | TokenHookMsg::Burn { amount, .. } => amount,
+            };
+            let allowed = amount.u128() <= POLICY_LIMIT;
+            to_binary(&PolicyResponse {
+                allowed,
+                reason: (!allowed).then(|| String::from("over the limit")),
+            })
+        }
+        HandlerQuery::Notified {} => {
+            to_binary(&NOTIFIED.may_load(deps.storage)?.unwrap_or_default())
+        }
+    }
+}
+
+fn handler_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(handler_execute, receiver_instantiate, handler_query);
+    Box::new(contract)
+}
+
+struct Suite {
+    app: App,
+    token: Addr,
+    receiver: Addr,
+    handler: Addr,
+}
+
+impl Suite {
+    fn new() -> Self {
+        let mut app = mock_app();
+        let token_id = app.store_code(token_contract());
+        let receiver_id = app.store_code(receiver_contract());
+        let handler_id = app.store_code(handler_contract());
+        let admin = Addr::unchecked(ADMIN);
+
+        let token = app
+            .instantiate_contract(
+                token_id,
+                admin.clone(),
+                &token_instantiate_msg(),
+                &[],
+                "token",
+                Some(ADMIN.to_string()),
+            )
+            .unwrap();
+        let receiver = app
+            .instantiate_contract(receiver_id, admin.clone(), &Empty {}, &[], "receiver", None)
+            .unwrap();
+        let handler = app
+            .instantiate_contract(handler_id, admin, &Empty {}, &[], "handler", None)
+            .unwrap();
+        Suite {
+            app,
+            token,
+            receiver,
+            handler,
+        }
+    }
+
+    fn execute(&mut self, sender: &str, msg: &Execute) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(Addr::unchecked(sender), self.token.clone(), msg, &[])
+    }
+
+    fn send(&mut self, amount: u128, msg: &[u8]) -> AnyResult<AppResponse> {
+        let msg = Execute::Send {
+            contract: self.receiver.to_string(),
+            amount: Uint128::new(amount),
+            msg: Binary::from(msg),
+        };
+        self.execute(OWNER, &msg)
+    }
+
+    fn balance(&self, address: &Addr) -> Uint128 {
+        let query = Query::Balance {
+            address: address.to_string(),
+        };
+        let res: BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(&self.token, &query)
+            .unwrap();
+        res.balance
+    }
+
+    fn received(&self) -> Vec<Cw20ReceiveMsg> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.receiver, &Empty {})
+            .unwrap()
+    }
+
+    fn notified(&self) -> Vec<TokenHookMsg> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.handler, &HandlerQuery::Notified {})
+            .unwrap()
+    }
+}
+
+#[test]
+fn send_reaches_receiver() {
+    let mut suite = Suite::new();
+    let msg = Execute::AddTokenHook {
+        address: suite.handler.to_string(),
+    };
+    suite.execute(ADMIN, &msg).unwrap();
+
+    suite.send(700, br#"{"deposit":{}}"#).unwrap();
+    assert_eq!(
+        suite.received(),
+        vec![Cw20ReceiveMsg {
+            sender: OWNER.to_string(),
+            amount: Uint128::new(700),
+            msg: Binary::from(br#"{"deposit":{}}"#),
+        }]
+    );
+    let owner = Addr::unchecked(OWNER);
+    assert_eq!(suite.balance(&owner), Uint128::new(9300));
+    assert_eq!(suite.balance(&suite.receiver), Uint128::new(700));
+
+    // the hook only hears of the send once the receiver accepted it
+    assert_eq!(
+        suite.notified(),
+        vec![TokenHookMsg::Transfer {
+            sender: OWNER.to_string(),
+            recipient: suite.receiver.to_string(),
+            amount: Uint128::new(700),
+        }]
+    );
+}
+
+#[test]
+fn failed_receive_refunds_send() {
+    let mut suite = Suite::new();
+    let msg = Execute::AddTokenHook {
+        address: suite.handler.to_string(),
+    };
+    suite.execute(ADMIN, &msg).unwrap();
+
+    let res = suite.send(700, b"fail").unwrap();
+    let event = Event::new("wasm-send_failed")
+        .add_attribute("owner", OWNER)
+        .add_attribute("contract", suite.receiver.as_str())
+        .add_attribute("amount", "700");
+    res.assert_event(&event);
+
+    // the tokens went back to the owner and nobody was told of a transfer
+    assert_eq!(suite.balance(&Addr::unchecked(OWNER)), Uint128::new(10000));
+    assert_eq!(suite.balance(&suite.receiver), Uint128::zero());
+    assert!(suite.received().is_empty());
+    assert!(suite.notified().is_empty());
+}
+
+#[test]
+fn handler_decides_policy() {
+    let mut suite = Suite::new();
+    let msg = Execute::SetPolicyContract {
+        address: Some(suite.handler.to_string()),
+        fail_open: false,
+    };
+    suite.execute(ADMIN, &msg).unwrap();
+
+    suite.send(POLICY_LIMIT, b"{}").unwrap();
+    let err = suite.send(POLICY_LIMIT + 1, b"{}").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PolicyDenied {
+            reason: String::from("over the limit"),
+        }
+    );
+    assert_eq!(
+        suite.balance(&Addr::unchecked(OWNER)),
+        Uint128::new(10000 - POLICY_LIMIT)
+    );
+}
+
+#[test]
+fn migrate_from_previous_release() {
+    let mut suite = Suite::new();
+    let legacy_id = suite.app.store_code(legacy_token_contract());
+    let token_id = suite.app.store_code(token_contract());
+    let legacy = suite
+        .app
+        .instantiate_contract(
+            legacy_id,
+            Addr::unchecked(ADMIN),
+            &token_instantiate_msg(),
+            &[],
+            "legacy",
+            Some(ADMIN.to_string()),
+        )
+        .unwrap();
+
+    let res = suite
+        .app
+        .migrate_contract(
+            Addr::unchecked(ADMIN),
+            legacy.clone(),
+            &MigrateMsg { admin: None },
+            token_id,
+        )
+        .unwrap();
+    let event = Event::new("wasm")
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", "1.5.0")
+        .add_attribute("to_version", "1.6.0");
+    res.assert_event(&event);
+
+    // the supply cap made it over from the minter data
+    let mint = |amount: u128| Execute::Mint {
+        recipient: OWNER.to_string(),
+        amount: Uint128::new(amount),
+    };
+    let minter = Addr::unchecked(MINTER);
+    suite
+        .app
+        .execute_contract(minter.clone(), legacy.clone(), &mint(10000), &[])
+        .unwrap();
+    let err = suite
+        .app
+        .execute_contract(minter, legacy, &mint(1), &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CannotExceedCap {}
+    );
+}
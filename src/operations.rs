@@ -0,0 +1,421 @@
+//! Storage backed checks shared by every execute handler. They load what the pure rules
+//! in `logic` need and leave the decisions to them.
+
+use crate::{
+    error::ContractError,
+    logic::{self, Party, Payer, TransferCharges, TransferRules},
+    state::*,
+};
+use cosmwasm_std::{Addr, Env, Order, StdError, StdResult, Storage, Uint128};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+
+/// Returns the amount of `address` frozen at the current block time, adding up the
+/// manually frozen balance, the time-locked tranches that have not expired yet and
+/// the unvested part of its vesting schedule
+pub fn frozen_amount(
+    storage: &dyn Storage,
+    env: &Env,
+    address: &Addr,
+) -> Result<Uint128, ContractError> {
+    let frozen = FROZEN_BALANCES
+        .may_load(storage, address)?
+        .unwrap_or_default();
+    let tranches = FROZEN_TRANCHES
+        .may_load(storage, address)?
+        .unwrap_or_default();
+    let vesting = VESTING.may_load(storage, address)?;
+    logic::frozen_total(
+        frozen,
+        &tranches,
+        vesting.as_ref(),
+        env.block.time.seconds(),
+    )
+}
+
+/// Loads the contract wide settings the transfer checks apply
+pub fn transfer_rules(storage: &dyn Storage) -> StdResult<TransferRules> {
+    Ok(TransferRules {
+        policy: TRANSFER_POLICY.may_load(storage)?.unwrap_or_default(),
+        kyc_threshold: KYC_THRESHOLD.may_load(storage)?,
+        bal_cap: BALANCE_CAP.load(storage)?,
+    })
+}
+
+/// Loads what the transfer checks know of `address`. The whitelist and KYC attestation
+/// are only looked up when `rules` consult them.
+pub fn load_party(
+    storage: &dyn Storage,
+    address: &Addr,
+    rules: Option<&TransferRules>,
+) -> StdResult<Party> {
+    let (whitelisted, attested_until) = match rules {
+        Some(rules) => (
+            rules.policy != TransferPolicy::Open && WHITELIST.has(storage, address),
+            match rules.kyc_threshold {
+                Some(_) => KYC_ATTESTATIONS
+                    .may_load(storage, address)?
+                    .map(|attestation| attestation.expires),
+                None => None,
+            },
+        ),
+        None => (false, None),
+    };
+    Ok(Party {
+        address: address.to_string(),
+        balance: BALANCES.may_load(storage, address)?.unwrap_or_default(),
+        blacklisted: BLACKLIST.has(storage, address),
+        burn_address: BURN_ADDRESSES.has(storage, address),
+        whitelisted,
+        attested_until,
+    })
+}
+
+/// Loads what the transfer checks know of `address` as the account tokens move out of,
+/// `party` being what `load_party` returned for it
+pub fn load_payer(
+    storage: &dyn Storage,
+    env: &Env,
+    address: &Addr,
+    party: Party,
+) -> Result<Payer, ContractError> {
+    Ok(Payer {
+        party,
+        frozen: frozen_amount(storage, env, address)?,
+        account_frozen: ACCOUNT_FROZEN.has(storage, address),
+        locked_until: SELF_LOCKS.may_load(storage, address)?,
+    })
+}
+
+/// Fails if `address` is blacklisted
+pub fn ensure_not_blacklisted(storage: &dyn Storage, address: &Addr) -> Result<(), ContractError> {
+    if BLACKLIST.has(storage, address) {
+        return Err(ContractError::Blacklisted {});
+    }
+    Ok(())
+}
+
+/// Fails unless a transfer of `amount` from `sender` to `recipient` passes
+/// `logic::check_transfer` at the current block time and every account involved can
+/// checkpoint. Charges nothing.
+pub fn ensure_transfer(
+    storage: &dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+    charges: TransferCharges,
+) -> Result<(), ContractError> {
+    let collector = match charges.fee.is_zero() {
+        true => None,
+        false => Some(TRANSFER_FEE.load(storage)?.collector),
+    };
+    let mut accounts = vec![sender, recipient];
+    accounts.extend(collector.as_ref());
+    ensure_history_seeded(storage, &accounts)?;
+
+    let rules = transfer_rules(storage)?;
+    let party = load_party(storage, sender, Some(&rules))?;
+    let payer = load_payer(storage, env, sender, party)?;
+    let recipient = load_party(storage, recipient, Some(&rules))?;
+    let collector = match collector {
+        Some(collector) => Some(load_party(storage, &collector, None)?),
+        None => None,
+    };
+    let now = env.block.time.seconds();
+    logic::check_transfer(
+        &rules,
+        &payer,
+        &recipient,
+        collector.as_ref(),
+        amount,
+        charges,
+        now,
+    )
+}
+
+/// Fails unless a mint of `amount` to `recipient` passes `logic::check_mint` and the
+/// recipient can checkpoint
+pub fn ensure_mint(
+    storage: &dyn Storage,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    ensure_history_seeded(storage, &[recipient])?;
+    let rules = transfer_rules(storage)?;
+    let recipient = load_party(storage, recipient, Some(&rules))?;
+    logic::check_mint(&rules, &recipient, amount)
+}
+
+/// Fails if moving `amount` without a memo hash is above the travel rule threshold
+pub fn ensure_memo_not_required(
+    storage: &dyn Storage,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let threshold = TRAVEL_RULE_THRESHOLD.may_load(storage)?;
+    logic::ensure_memo_not_required(threshold, amount)
+}
+
+/// Fails if `address` is blacklisted, frozen outright, self locked or if moving `amount`
+/// out of it would dip into its frozen balance
+pub fn ensure_spendable(
+    storage: &dyn Storage,
+    env: &Env,
+    address: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let party = load_party(storage, address, None)?;
+    let payer = load_payer(storage, env, address, party)?;
+    logic::ensure_spendable(&payer, amount, env.block.time.seconds())
+}
+
+/// Fails if `recipient` is blacklisted or if receiving `amount` would push it over the
+/// balance cap. Registered burn addresses are not capped.
+pub fn ensure_cap(
+    storage: &dyn Storage,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    let recipient = load_party(storage, recipient, None)?;
+    logic::ensure_cap(&recipient, amount, BALANCE_CAP.load(storage)?)
+}
+
+/// Copies the current balances of `addresses` and the total supply into their snapshots
+/// at the current height, moving the voting power of their delegates along, settling the
+/// rewards earned by the previous balances and updating the account and supply stats.
+/// Must follow every change to them.
+///
+/// The contract's own balance only escrows stakes and unbonding claims: the stakes earn
+/// rewards for their owners and the contract is left out of the holder index.
+pub fn checkpoint(
+    storage: &mut dyn Storage,
+    env: &Env,
+    addresses: &[&Addr],
+) -> Result<(), ContractError> {
+    ensure_history_seeded(storage, addresses)?;
+    let height = env.block.height;
+    let changes = snapshot_balances(storage, height, addresses)?;
+    for (address, (previous, balance)) in addresses.iter().zip(changes.balances) {
+        let escrow = **address == env.contract.address;
+        if !escrow {
+            let earning = reward_weight(storage, address, previous)?;
+            settle_rewards(storage, address, earning)?;
+        }
+        if balance != previous {
+            record_activity(storage, height, address, previous, balance)?;
+            index_holder(storage, env, address, balance)?;
+        }
+    }
+
+    let (previous, total_supply) = changes.total_supply;
+    if total_supply != previous {
+        let mut stats = SUPPLY_STATS.may_load(storage)?.unwrap_or_default();
+        if total_supply > previous {
+            stats.minted = stats
+                .minted
+                .checked_add(total_supply - previous)
+                .map_err(StdError::from)?;
+        } else {
+            stats.burned = stats
+                .burned
+                .checked_add(previous - total_supply)
+                .map_err(StdError::from)?;
+        }
+        SUPPLY_STATS.save(storage, &stats)?;
+    }
+    Ok(())
+}
+
+/// Keeps `address` in `holders()` while it holds tokens. The contract's own balance only
+/// escrows stakes and burn addresses hold tokens out of circulation, so neither is a holder.
+pub fn index_holder(
+    storage: &mut dyn Storage,
+    env: &Env,
+    address: &Addr,
+    balance: Uint128,
+) -> StdResult<()> {
+    if balance.is_zero() || *address == env.contract.address || BURN_ADDRESSES.has(storage, address)
+    {
+        holders().remove(storage, address)
+    } else {
+        holders().save(storage, address, &balance)
+    }
+}
+
+/// Fails for accounts the backfill of a 1.2.0 migration has not reached yet: their
+/// balance before the change was never snapshotted, so their history cannot go on from it.
+/// `checkpoint` enforces it, the checks run it early to fail before any write.
+fn ensure_history_seeded(storage: &dyn Storage, addresses: &[&Addr]) -> Result<(), ContractError> {
+    let start_after = match BACKFILL.may_load(storage)? {
+        Some(backfill) if backfill.seed_history => backfill.start_after,
+        _ => return Ok(()),
+    };
+    match addresses
+        .iter()
+        .find(|address| start_after.as_ref().is_none_or(|last| **address > last))
+    {
+        Some(address) => Err(ContractError::MigrationPending {
+            address: address.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Balances as snapshotted before and after a `snapshot_balances`
+pub struct BalanceChanges {
+    /// previous and current balance of each address, in order
+    pub balances: Vec<(Uint128, Uint128)>,
+    /// previous and current total supply
+    pub total_supply: (Uint128, Uint128),
+}
+
+/// Copies the current balances of `addresses` and the total supply into their snapshots
+/// at `height`, moving the voting power of their delegates along. Unlike `checkpoint` it
+/// records no activity, stats or rewards, so migrations can seed the history with it.
+pub fn snapshot_balances(
+    storage: &mut dyn Storage,
+    height: u64,
+    addresses: &[&Addr],
+) -> StdResult<BalanceChanges> {
+    let mut balances = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        let previous = BALANCE_SNAPSHOTS
+            .may_load(storage, address)?
+            .unwrap_or_default();
+        let balance = BALANCES.may_load(storage, address)?.unwrap_or_default();
+        BALANCE_SNAPSHOTS.save(storage, address, &balance, height)?;
+
+        let delegate = delegate_of(storage, address)?;
+        let power = VOTING_POWER
+            .may_load(storage, &delegate)?
+            .unwrap_or_default()
+            .checked_add(balance)?
+            .checked_sub(previous)?;
+        VOTING_POWER.save(storage, &delegate, &power, height)?;
+        balances.push((previous, balance));
+    }
+
+    let previous = TOTAL_SUPPLY_SNAPSHOTS
+        .may_load(storage, TOTAL_SUPPLY_KEY)?
+        .unwrap_or_default();
+    let total_supply = TOKEN_INFO.load(storage)?.total_supply;
+    TOTAL_SUPPLY_SNAPSHOTS.save(storage, TOTAL_SUPPLY_KEY, &total_supply, height)?;
+    Ok(BalanceChanges {
+        balances,
+        total_supply: (previous, total_supply),
+    })
+}
+
+fn record_activity(
+    storage: &mut dyn Storage,
+    height: u64,
+    address: &Addr,
+    previous: Uint128,
+    balance: Uint128,
+) -> StdResult<()> {
+    let mut stats = ACCOUNT_STATS
+        .may_load(storage, address)?
+        .unwrap_or_default();
+    stats.tx_count += 1;
+    if balance > previous {
+        stats.total_received = stats.total_received.checked_add(balance - previous)?;
+    } else {
+        stats.total_sent = stats.total_sent.checked_add(previous - balance)?;
+    }
+    stats.last_activity_height = height;
+    ACCOUNT_STATS.save(storage, address, &stats)
+}
+
+/// Keeps the frozen total of the supply stats in line with a manually frozen balance
+/// going from `previous` to `frozen`
+pub fn update_frozen_total(
+    storage: &mut dyn Storage,
+    previous: Uint128,
+    frozen: Uint128,
+) -> StdResult<()> {
+    let mut stats = SUPPLY_STATS.may_load(storage)?.unwrap_or_default();
+    stats.frozen = stats.frozen.checked_add(frozen)?.checked_sub(previous)?;
+    SUPPLY_STATS.save(storage, &stats)
+}
+
+/// Returns the address voting with the balance of `address`
+pub fn delegate_of(storage: &dyn Storage, address: &Addr) -> StdResult<Addr> {
+    Ok(DELEGATES
+        .may_load(storage, address)?
+        .unwrap_or_else(|| address.clone()))
+}
+
+/// Returns the amount `address` earns rewards on while holding `balance`, which includes
+/// what it has staked. Burn addresses earn nothing.
+pub fn reward_weight(
+    storage: &dyn Storage,
+    address: &Addr,
+    balance: Uint128,
+) -> StdResult<Uint128> {
+    if BURN_ADDRESSES.has(storage, address) {
+        return Ok(Uint128::zero());
+    }
+    let staked = STAKES.may_load(storage, address)?.unwrap_or_default();
+    Ok(balance.checked_add(staked)?)
+}
+
+/// Returns the sum of every `reward_weight`: the total supply without the contract's own
+/// balance, except the stakes it escrows, and without the burn addresses' balances
+pub fn earning_supply(storage: &dyn Storage, env: &Env) -> StdResult<Uint128> {
+    let total_supply = TOKEN_INFO.load(storage)?.total_supply;
+    let escrowed = BALANCES
+        .may_load(storage, &env.contract.address)?
+        .unwrap_or_default();
+    let staked = TOTAL_STAKED.may_load(storage)?.unwrap_or_default();
+    let burned = BURN_ADDRESSES
+        .keys(storage, None, None, Order::Ascending)
+        .map(|key| {
+            let address = Addr::unchecked(String::from_utf8(key)?);
+            Ok(BALANCES.may_load(storage, &address)?.unwrap_or_default())
+        })
+        .sum::<StdResult<Uint128>>()?;
+    Ok(total_supply
+        .checked_sub(escrowed)?
+        .checked_add(staked)?
+        .checked_sub(burned)?)
+}
+
+/// Credits `address` with the rewards its `balance` earned since its last settlement
+pub fn settle_rewards(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    balance: Uint128,
+) -> StdResult<UserRewards> {
+    let rewards = pending_rewards(storage, address, balance)?;
+    USER_REWARDS.save(storage, address, &rewards)?;
+    Ok(rewards)
+}
+
+/// Returns the rewards of `address` as if settled now with `balance`, without saving them
+pub fn pending_rewards(
+    storage: &dyn Storage,
+    address: &Addr,
+    balance: Uint128,
+) -> StdResult<UserRewards> {
+    let index = REWARD_INDEX.may_load(storage)?.unwrap_or_default();
+    let rewards = USER_REWARDS.may_load(storage, address)?.unwrap_or_default();
+    Ok(UserRewards {
+        index,
+        pending: rewards
+            .pending
+            .checked_add(balance * (index - rewards.index))?,
+    })
+}
+
+/// Returns the fee and burn tax `payer` is charged to move `amount` to `recipient`,
+/// nothing when either of them is exempt
+pub fn transfer_charges(
+    storage: &dyn Storage,
+    payer: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<TransferCharges> {
+    let exempt = FEE_EXEMPTIONS.has(storage, payer) || FEE_EXEMPTIONS.has(storage, recipient);
+    let fee_bps = TRANSFER_FEE.may_load(storage)?.map(|config| config.bps);
+    let burn_rate = BURN_RATE.may_load(storage)?.unwrap_or_default();
+    Ok(logic::transfer_charges(exempt, fee_bps, burn_rate, amount))
+}
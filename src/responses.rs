@@ -0,0 +1,278 @@
+//! Responses of the custom queries in `msg::Query`, re-exported from `msg`. The cw20 ones
+//! come from the `cw20` crate.
+
+use crate::state::{
+    Attestation, FrozenTranche, PendingAction, Role, TransferMemo, TransferPolicy, VestingSchedule,
+};
+use cosmwasm_std::{Addr, Uint128};
+use cw0::Expiration;
+use cw20::Cw20Coin;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalSupplyAtResponse {
+    pub total_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelegationResponse {
+    pub delegate: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotingPowerResponse {
+    pub power: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinterInfo {
+    pub minter: String,
+    pub cap: Option<Uint128>,
+    pub minted: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintersResponse {
+    pub minters: Vec<MinterInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferFeeResponse {
+    pub fee: Uint128,
+    pub burned: Uint128,
+    /// amount left for the recipient
+    pub amount_received: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BurnStatsResponse {
+    pub burn_rate_bps: u16,
+    /// cumulative amount destroyed by the burn tax, explicit burns excluded
+    pub total_burned: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AirdropStageResponse {
+    pub stage: u8,
+    pub merkle_root: String,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsClaimedResponse {
+    pub claimed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedResponse {
+    pub stake: Uint128,
+    /// rate limit including the staking boost, unset when there is no limit
+    pub rate_limit: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRewardsResponse {
+    /// unset until a reward denom is configured
+    pub denom: Option<String>,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CirculatingSupplyResponse {
+    pub total_supply: Uint128,
+    /// sum of balances held by registered burn addresses
+    pub burned: Uint128,
+    pub circulating_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CapabilitiesResponse {
+    pub extensions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FrozenBalanceResponse {
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalanceAtResponse {
+    pub balance: Uint128,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllFrozenBalancesResponse {
+    pub frozen_balances: Vec<Cw20Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopHoldersResponse {
+    pub holders: Vec<Cw20Coin>,
+    /// false while a migration backfill has not gone through every account yet
+    pub complete: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendableBalanceResponse {
+    pub balance: Uint128,
+    pub frozen: Uint128,
+    /// amount that can be moved out right now
+    pub spendable: Uint128,
+    /// amount that can still be received before hitting the balance cap
+    pub cap_headroom: Uint128,
+}
+
+#[cfg(feature = "migration-preview")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrationPreviewResponse {
+    pub from_version: String,
+    pub to_version: String,
+    pub namespaces: Vec<NamespacePreview>,
+}
+
+#[cfg(feature = "migration-preview")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NamespacePreview {
+    pub namespace: String,
+    /// number of entries the migration would write
+    pub affected_entries: u64,
+    /// keys of entries that would fail validation during the migration
+    pub invalid_entries: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouterResponse {
+    pub max_per_tx: Uint128,
+    pub expiry: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouterOptInResponse {
+    pub opted_in: bool,
+    /// what the router can still move for the owner
+    pub allowance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FrozenScheduleResponse {
+    pub tranches: Vec<FrozenTranche>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsFrozenResponse {
+    pub frozen: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingInfoResponse {
+    pub schedule: Option<VestingSchedule>,
+    pub vested: Uint128,
+    pub unvested: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsBlacklistedResponse {
+    pub blacklisted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsWhitelistedResponse {
+    pub whitelisted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AttestationResponse {
+    pub attestation: Option<Attestation>,
+    /// whether the attestation has not expired yet
+    pub valid: bool,
+    pub kyc_threshold: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferMemosResponse {
+    pub threshold: Option<Uint128>,
+    pub memos: Vec<TransferMemo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SupplyBreakdownResponse {
+    pub total_supply: Uint128,
+    /// total supply neither escrowed nor frozen
+    pub circulating_supply: Uint128,
+    /// held by this contract for stakes and unbonding claims
+    pub escrowed: Uint128,
+    /// manually frozen balances, time-locked tranches and vesting excluded
+    pub frozen: Uint128,
+    pub total_minted: Uint128,
+    pub total_burned: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccountStatsResponse {
+    pub tx_count: u64,
+    pub total_sent: Uint128,
+    pub total_received: Uint128,
+    pub last_activity_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferPolicyResponse {
+    pub policy: TransferPolicy,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PolicyContractResponse {
+    pub address: Option<String>,
+    pub fail_open: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenHooksResponse {
+    pub hooks: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub roles: Vec<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingActionsResponse {
+    pub delay: u64,
+    pub actions: Vec<PendingAction>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RiskSnapshotResponse {
+    pub balance: Uint128,
+    /// frozen balance, time-locked tranches and unvested amount combined
+    pub frozen: Uint128,
+    pub cap_headroom: Uint128,
+    /// amount moved out within the current rate limit window
+    pub outbound_volume: Uint128,
+    pub rate_limit: Option<Uint128>,
+    pub rate_limit_exempt: bool,
+    pub account_frozen: bool,
+    pub blacklisted: bool,
+    pub burn_address: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SelfLockResponse {
+    /// time (in seconds) until which the account is locked, if still locked
+    pub locked_until: Option<u64>,
+    pub recovery_address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PermitNonceResponse {
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuthorizationStateResponse {
+    pub used: bool,
+}
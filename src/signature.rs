@@ -0,0 +1,43 @@
+//! Verification of payloads signed offline by token holders
+
+use crate::error::ContractError;
+use bech32::{ToBase32, Variant};
+use cosmwasm_std::{to_vec, Addr, Api, Binary};
+use ripemd160::Ripemd160;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Returns the bech32 address with prefix `hrp` controlled by a compressed secp256k1 `pubkey`
+pub fn pubkey_to_address(pubkey: &[u8], hrp: &str) -> Result<String, ContractError> {
+    let hash = Ripemd160::digest(&Sha256::digest(pubkey));
+    bech32::encode(hrp, hash.to_base32(), Variant::Bech32)
+        .map_err(|_| ContractError::InvalidPubkey {})
+}
+
+/// Fails unless `pubkey` controls `signer` and `signature` signs the JSON encoding of `payload`
+pub fn verify_signature<T: Serialize>(
+    api: &dyn Api,
+    signer: &Addr,
+    pubkey: &Binary,
+    payload: &T,
+    signature: &Binary,
+) -> Result<(), ContractError> {
+    // the address prefix is taken from the signer itself, so any chain works
+    let hrp = signer
+        .as_str()
+        .rsplit_once('1')
+        .map(|(hrp, _)| hrp)
+        .ok_or(ContractError::InvalidPubkey {})?;
+    if pubkey_to_address(pubkey, hrp)? != signer.as_str() {
+        return Err(ContractError::InvalidPubkey {});
+    }
+
+    let hash = Sha256::digest(&to_vec(payload)?);
+    if !api
+        .secp256k1_verify(&hash, signature, pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?
+    {
+        return Err(ContractError::InvalidSignature {});
+    }
+    Ok(())
+}
@@ -0,0 +1,314 @@
+//! In-memory replica of the contract's balance rules, so sequencers and risk tools can
+//! replay transactions off-chain. It relies on the same pure functions the contract
+//! handlers use, hence both reach the same outcome for the same state and input.
+
+use crate::{
+    error::ContractError,
+    logic::{self, Party, Payer, TransferCharges, TransferRules},
+    state::*,
+};
+use cosmwasm_std::{Decimal, Deps, Order, StdError, StdResult, Uint128};
+use cw20_base::state::{BALANCES, TOKEN_INFO};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Ledger {
+    pub balances: BTreeMap<String, Uint128>,
+    pub frozen_balances: BTreeMap<String, Uint128>,
+    pub frozen_tranches: BTreeMap<String, Vec<FrozenTranche>>,
+    pub frozen_accounts: BTreeSet<String>,
+    pub blacklist: BTreeSet<String>,
+    pub transfer_policy: TransferPolicy,
+    pub whitelist: BTreeSet<String>,
+    pub attestations: BTreeMap<String, Attestation>,
+    pub kyc_threshold: Option<Uint128>,
+    pub travel_rule_threshold: Option<Uint128>,
+    pub self_locks: BTreeMap<String, u64>,
+    pub vesting: BTreeMap<String, VestingSchedule>,
+    pub bal_cap: Uint128,
+    pub burn_addresses: BTreeSet<String>,
+    pub rate_limit: Option<Uint128>,
+    pub rate_limit_exemptions: BTreeSet<String>,
+    pub rate_limits: BTreeMap<String, RateLimitWindow>,
+    pub stakes: BTreeMap<String, Uint128>,
+    pub transfer_fee: Option<TransferFeeConfig>,
+    pub fee_exemptions: BTreeSet<String>,
+    pub burn_rate: u16,
+    pub rate_limit_boost: Decimal,
+    pub total_supply: Uint128,
+    /// supply cap, kept even without a cw20 minter
+    pub mint_cap: Option<Uint128>,
+}
+
+impl Ledger {
+    /// Snapshots the contract state into memory
+    pub fn load(deps: Deps) -> StdResult<Self> {
+        let token_info = TOKEN_INFO.load(deps.storage)?;
+
+        Ok(Ledger {
+            balances: load_map(BALANCES.range(deps.storage, None, None, Order::Ascending))?,
+            frozen_balances: load_map(FROZEN_BALANCES.range(
+                deps.storage,
+                None,
+                None,
+                Order::Ascending,
+            ))?,
+            frozen_tranches: load_map(FROZEN_TRANCHES.range(
+                deps.storage,
+                None,
+                None,
+                Order::Ascending,
+            ))?,
+            frozen_accounts: load_set(ACCOUNT_FROZEN.keys(
+                deps.storage,
+                None,
+                None,
+                Order::Ascending,
+            ))?,
+            blacklist: load_set(BLACKLIST.keys(deps.storage, None, None, Order::Ascending))?,
+            transfer_policy: TRANSFER_POLICY.may_load(deps.storage)?.unwrap_or_default(),
+            whitelist: load_set(WHITELIST.keys(deps.storage, None, None, Order::Ascending))?,
+            attestations: load_map(KYC_ATTESTATIONS.range(
+                deps.storage,
+                None,
+                None,
+                Order::Ascending,
+            ))?,
+            kyc_threshold: KYC_THRESHOLD.may_load(deps.storage)?,
+            travel_rule_threshold: TRAVEL_RULE_THRESHOLD.may_load(deps.storage)?,
+            self_locks: load_map(SELF_LOCKS.range(deps.storage, None, None, Order::Ascending))?,
+            vesting: load_map(VESTING.range(deps.storage, None, None, Order::Ascending))?,
+            bal_cap: BALANCE_CAP.load(deps.storage)?,
+            burn_addresses: load_set(BURN_ADDRESSES.keys(
+                deps.storage,
+                None,
+                None,
+                Order::Ascending,
+            ))?,
+            rate_limit: RATE_LIMIT.may_load(deps.storage)?,
+            rate_limit_exemptions: load_set(RATE_LIMIT_EXEMPTIONS.keys(
+                deps.storage,
+                None,
+                None,
+                Order::Ascending,
+            ))?,
+            rate_limits: load_map(RATE_LIMITS.range(deps.storage, None, None, Order::Ascending))?,
+            stakes: load_map(STAKES.range(deps.storage, None, None, Order::Ascending))?,
+            transfer_fee: TRANSFER_FEE.may_load(deps.storage)?,
+            fee_exemptions: load_set(FEE_EXEMPTIONS.keys(
+                deps.storage,
+                None,
+                None,
+                Order::Ascending,
+            ))?,
+            burn_rate: BURN_RATE.may_load(deps.storage)?.unwrap_or_default(),
+            rate_limit_boost: STAKING_CONFIG
+                .may_load(deps.storage)?
+                .unwrap_or_default()
+                .rate_limit_boost,
+            total_supply: token_info.total_supply,
+            mint_cap: SUPPLY_CAP.may_load(deps.storage)?,
+        })
+    }
+
+    pub fn balance(&self, address: &str) -> Uint128 {
+        self.balances.get(address).copied().unwrap_or_default()
+    }
+
+    /// Returns the manually frozen balance plus the tranches still locked and the amount
+    /// still unvested at `now`
+    pub fn frozen_balance(&self, address: &str, now: u64) -> Result<Uint128, ContractError> {
+        let frozen = self
+            .frozen_balances
+            .get(address)
+            .copied()
+            .unwrap_or_default();
+        let tranches = self
+            .frozen_tranches
+            .get(address)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        logic::frozen_total(frozen, tranches, self.vesting.get(address), now)
+    }
+
+    /// Mirrors `Execute::Transfer` sent by `sender` at block time `now` (in seconds)
+    pub fn transfer(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: Uint128,
+        now: u64,
+    ) -> Result<(), ContractError> {
+        logic::ensure_memo_not_required(self.travel_rule_threshold, amount)?;
+        self.transfer_with_memo(sender, recipient, amount, now)
+    }
+
+    /// Mirrors `Execute::TransferWithMemo` sent by `sender` at block time `now` (in seconds),
+    /// assuming a well-formed memo hash
+    pub fn transfer_with_memo(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: Uint128,
+        now: u64,
+    ) -> Result<(), ContractError> {
+        let charges = self.transfer_charges(sender, recipient, amount);
+        let collector = match &self.transfer_fee {
+            Some(config) if !charges.fee.is_zero() => Some(config.collector.to_string()),
+            _ => None,
+        };
+        logic::check_transfer(
+            &self.rules(),
+            &self.payer(sender, now)?,
+            &self.party(recipient),
+            collector
+                .as_deref()
+                .map(|collector| self.party(collector))
+                .as_ref(),
+            amount,
+            charges,
+            now,
+        )?;
+        self.consume_rate_limit(sender, amount, now)?;
+
+        let net = amount - charges.total();
+        if net.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let sender_balance = self
+            .balance(sender)
+            .checked_sub(amount)
+            .map_err(StdError::from)?;
+        self.balances.insert(sender.to_string(), sender_balance);
+        self.credit(recipient, net)?;
+        if let Some(collector) = collector {
+            self.credit(&collector, charges.fee)?;
+        }
+        self.total_supply = self
+            .total_supply
+            .checked_sub(charges.burned)
+            .map_err(StdError::from)?;
+        Ok(())
+    }
+
+    /// Returns the fee and burn tax `sender` is charged to move `amount` to `recipient`
+    pub fn transfer_charges(
+        &self,
+        sender: &str,
+        recipient: &str,
+        amount: Uint128,
+    ) -> TransferCharges {
+        let exempt =
+            self.fee_exemptions.contains(sender) || self.fee_exemptions.contains(recipient);
+        let fee_bps = self.transfer_fee.as_ref().map(|config| config.bps);
+        logic::transfer_charges(exempt, fee_bps, self.burn_rate, amount)
+    }
+
+    fn credit(&mut self, address: &str, amount: Uint128) -> Result<(), ContractError> {
+        let balance = self
+            .balance(address)
+            .checked_add(amount)
+            .map_err(StdError::from)?;
+        self.balances.insert(address.to_string(), balance);
+        Ok(())
+    }
+
+    /// Mirrors `Execute::Burn` sent by `sender` at block time `now` (in seconds)
+    pub fn burn(&mut self, sender: &str, amount: Uint128, now: u64) -> Result<(), ContractError> {
+        logic::ensure_spendable(&self.payer(sender, now)?, amount, now)?;
+
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let balance = self
+            .balance(sender)
+            .checked_sub(amount)
+            .map_err(StdError::from)?;
+        self.balances.insert(sender.to_string(), balance);
+        self.total_supply = self
+            .total_supply
+            .checked_sub(amount)
+            .map_err(StdError::from)?;
+        Ok(())
+    }
+
+    /// Mirrors `Execute::Mint`, assuming it is sent by the authorized minter
+    pub fn mint(&mut self, recipient: &str, amount: Uint128) -> Result<(), ContractError> {
+        logic::check_mint(&self.rules(), &self.party(recipient), amount)?;
+
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        self.total_supply = logic::ensure_mint_cap(self.total_supply, amount, self.mint_cap)?;
+        self.credit(recipient, amount)
+    }
+
+    fn rules(&self) -> TransferRules {
+        TransferRules {
+            policy: self.transfer_policy,
+            kyc_threshold: self.kyc_threshold,
+            bal_cap: self.bal_cap,
+        }
+    }
+
+    fn party(&self, address: &str) -> Party {
+        Party {
+            address: address.to_string(),
+            balance: self.balance(address),
+            blacklisted: self.blacklist.contains(address),
+            burn_address: self.burn_addresses.contains(address),
+            whitelisted: self.whitelist.contains(address),
+            attested_until: self
+                .attestations
+                .get(address)
+                .map(|attestation| attestation.expires),
+        }
+    }
+
+    fn payer(&self, address: &str, now: u64) -> Result<Payer, ContractError> {
+        Ok(Payer {
+            party: self.party(address),
+            frozen: self.frozen_balance(address, now)?,
+            account_frozen: self.frozen_accounts.contains(address),
+            locked_until: self.self_locks.get(address).copied(),
+        })
+    }
+
+    fn consume_rate_limit(
+        &mut self,
+        address: &str,
+        amount: Uint128,
+        now: u64,
+    ) -> Result<(), ContractError> {
+        let limit = match self.rate_limit {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        let exempt = self.rate_limit_exemptions.contains(address);
+        let staked = self.stakes.get(address).copied().unwrap_or_default();
+        let window = self.rate_limits.get(address).cloned().unwrap_or_default();
+        let boost = self.rate_limit_boost;
+        if let Some(window) =
+            logic::consume_rate_limit(window, limit, exempt, staked, boost, now, amount)?
+        {
+            self.rate_limits.insert(address.to_string(), window);
+        }
+        Ok(())
+    }
+}
+
+fn load_map<T>(
+    items: impl Iterator<Item = StdResult<(Vec<u8>, T)>>,
+) -> StdResult<BTreeMap<String, T>> {
+    items
+        .map(|item| {
+            let (key, value) = item?;
+            Ok((String::from_utf8(key)?, value))
+        })
+        .collect()
+}
+
+fn load_set(keys: impl Iterator<Item = Vec<u8>>) -> StdResult<BTreeSet<String>> {
+    keys.map(|key| String::from_utf8(key).map_err(StdError::from))
+        .collect()
+}
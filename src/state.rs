@@ -1,5 +1,389 @@
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw0::Expiration;
+use cw_controllers::{Claims, Hooks};
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, Map, MultiIndex, SnapshotMap, Strategy, U128Key, U64Key,
+    U8Key,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 pub const FROZEN_BALANCES: Map<&Addr, Uint128> = Map::new("frozen_balances");
+/// Frozen amounts that unlock by themselves at a given time
+pub const FROZEN_TRANCHES: Map<&Addr, Vec<FrozenTranche>> = Map::new("frozen_tranches");
 pub const BALANCE_CAP: Item<Uint128> = Item::new("balance_cap");
+/// Single admin of 1.1.0 deployments, moved into `ROLES` when migrating to 1.2.0
+pub const ADMIN: Item<Addr> = Item::new("admin");
+/// (address, role) pairs that were granted a role
+pub const ROLES: Map<(&Addr, U8Key), bool> = Map::new("roles");
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// grants and revokes roles, manages token wide settings
+    Admin,
+    Pauser,
+    /// freezes, blacklists and claws back accounts
+    Compliance,
+    RelayerAdmin,
+    FeeManager,
+    /// records KYC attestations
+    KycProvider,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Role::Admin,
+        Role::Pauser,
+        Role::Compliance,
+        Role::RelayerAdmin,
+        Role::FeeManager,
+        Role::KycProvider,
+    ];
+
+    pub fn key(self) -> U8Key {
+        U8Key::from(self as u8)
+    }
+}
+
+/// Accounts frozen outright, which cannot move any funds out
+pub const ACCOUNT_FROZEN: Map<&Addr, bool> = Map::new("account_frozen");
+
+/// Who may send and receive tokens, anyone unless set
+pub const TRANSFER_POLICY: Item<TransferPolicy> = Item::new("transfer_policy");
+/// Accounts allowed to move tokens under a restrictive `TransferPolicy`
+pub const WHITELIST: Map<&Addr, bool> = Map::new("whitelist");
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferPolicy {
+    #[default]
+    Open,
+    /// both the sender and the recipient have to be whitelisted
+    WhitelistedOnly,
+    /// only the recipient has to be whitelisted
+    WhitelistedRecipients,
+}
+
+/// KYC attestations recorded by KYC providers
+pub const KYC_ATTESTATIONS: Map<&Addr, Attestation> = Map::new("kyc_attestations");
+/// Transfers above this amount need both parties attested, unset means never
+pub const KYC_THRESHOLD: Item<Uint128> = Item::new("kyc_threshold");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Attestation {
+    pub level: u8,
+    /// block time (in seconds) from which the attestation is no longer valid
+    pub expires: u64,
+}
+
+/// Plain transfers above this amount are rejected in favour of `Execute::TransferWithMemo`,
+/// unset means never
+pub const TRAVEL_RULE_THRESHOLD: Item<Uint128> = Item::new("travel_rule_threshold");
+/// Transfers made with a memo hash, by id
+pub const TRANSFER_MEMOS: Map<U64Key, TransferMemo> = Map::new("transfer_memos");
+/// Id given to the next transfer memo
+pub const NEXT_MEMO_ID: Item<u64> = Item::new("next_memo_id");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferMemo {
+    pub id: u64,
+    pub from: Addr,
+    pub to: Addr,
+    pub amount: Uint128,
+    /// hex encoded sha256 hash of the off-chain travel rule payload
+    pub memo_hash: String,
+    pub height: u64,
+}
+
+/// Activity aggregates per account, counted from its first checkpoint
+pub const ACCOUNT_STATS: Map<&Addr, AccountStats> = Map::new("account_stats");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct AccountStats {
+    /// number of operations that changed the balance
+    pub tx_count: u64,
+    pub total_sent: Uint128,
+    pub total_received: Uint128,
+    pub last_activity_height: u64,
+}
+
+/// Running supply counters, so the supply breakdown needs no iteration
+pub const SUPPLY_STATS: Item<SupplyStats> = Item::new("supply_stats");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct SupplyStats {
+    pub minted: Uint128,
+    pub burned: Uint128,
+    /// manually frozen balances, time-locked tranches and vesting excluded
+    pub frozen: Uint128,
+}
+
+/// Contracts notified of every transfer, mint and burn
+pub const TOKEN_HOOKS: Hooks = Hooks::new("token_hooks");
+
+/// External contract consulted before every transfer, mint and burn
+pub const POLICY_CONTRACT: Item<PolicyContract> = Item::new("policy_contract");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PolicyContract {
+    pub address: Addr,
+    /// whether actions go through while the policy contract cannot be queried
+    pub fail_open: bool,
+}
+
+pub struct HolderIndexes<'a> {
+    /// holders ordered by balance, then by address
+    pub balance: MultiIndex<'a, (U128Key, Vec<u8>), Uint128>,
+}
+
+impl<'a> IndexList<Uint128> for HolderIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Uint128>> + '_> {
+        let v: Vec<&dyn Index<Uint128>> = vec![&self.balance];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Non-zero balances indexed by size, so the largest holders can be listed without
+/// scanning every account. Mirrors `BALANCES` and is kept in sync by `checkpoint`.
+pub fn holders<'a>() -> IndexedMap<'a, &'a Addr, Uint128, HolderIndexes<'a>> {
+    let indexes = HolderIndexes {
+        balance: MultiIndex::new(
+            |balance, pk| (U128Key::from(balance.u128()), pk),
+            "holders",
+            "holders__balance",
+        ),
+    };
+    IndexedMap::new("holders", indexes)
+}
+
+/// Accounts a migration still has to go through, present until `Execute::MigrateHolders`
+/// has reached the last one
+pub const BACKFILL: Item<Backfill> = Item::new("backfill");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Backfill {
+    /// whether the balance history is seeded along with the holder index
+    pub seed_history: bool,
+    /// last account gone through
+    pub start_after: Option<Addr>,
+}
+
+/// Canonical burn/sink addresses, excluded from circulating supply and cap checks
+pub const BURN_ADDRESSES: Map<&Addr, bool> = Map::new("burn_addresses");
+
+/// Length of a rate limit window in seconds
+pub const RATE_LIMIT_WINDOW: u64 = 24 * 60 * 60;
+
+/// Maximum amount an address can move out within a single window, unset means unlimited
+pub const RATE_LIMIT: Item<Uint128> = Item::new("rate_limit");
+pub const RATE_LIMITS: Map<&Addr, RateLimitWindow> = Map::new("rate_limits");
+pub const RATE_LIMIT_EXEMPTIONS: Map<&Addr, bool> = Map::new("rate_limit_exemptions");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct RateLimitWindow {
+    /// block time (in seconds) at which the current window started
+    pub window_start: u64,
+    /// amount moved out within the current window
+    pub consumed: Uint128,
+}
+
+/// Delay (in seconds) sensitive admin actions have to wait once scheduled. While it is
+/// non-zero those actions can only be applied through the schedule.
+pub const TIMELOCK_DELAY: Item<u64> = Item::new("timelock_delay");
+/// Admin actions scheduled for later execution, by id
+pub const PENDING_ACTIONS: Map<U64Key, PendingAction> = Map::new("pending_actions");
+/// Id given to the next scheduled action
+pub const NEXT_ACTION_ID: Item<u64> = Item::new("next_action_id");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub enum ScheduledAction {
+    UpdateBalanceCap { new_cap: Uint128 },
+    SetRateLimit { limit: Option<Uint128> },
+    SetTimelockDelay { delay: u64 },
+    SetTransferPolicy { policy: TransferPolicy },
+    SetTransferFee { bps: u16, collector: String },
+    SetBurnRate { bps: u16 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAction {
+    pub id: u64,
+    pub action: ScheduledAction,
+    /// block time (in seconds) from which the action can be executed
+    pub eta: u64,
+}
+
+/// Sends whose receiving contract has not replied yet, by reply id
+pub const PENDING_SENDS: Map<U64Key, PendingSend> = Map::new("pending_sends");
+/// Reply id given to the next send
+pub const NEXT_SEND_ID: Item<u64> = Item::new("next_send_id");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingSend {
+    pub owner: Addr,
+    pub contract: Addr,
+    /// amount the receiving contract got
+    pub amount: Uint128,
+    /// transfer fee paid to the fee collector, if any
+    pub fee: Option<(Addr, Uint128)>,
+    /// burn tax destroyed
+    pub burned: Uint128,
+    /// amount counted against the owner's rate limit
+    pub rate_limited: Uint128,
+}
+
+/// Next nonce expected in a permit signed by each owner
+pub const PERMIT_NONCES: Map<&Addr, u64> = Map::new("permit_nonces");
+
+/// Nonces of the transfer authorizations already used by each signer
+pub const USED_AUTHORIZATIONS: Map<(&Addr, &[u8]), bool> = Map::new("used_authorizations");
+
+/// Balance history, written alongside `BALANCES` on every change
+pub const BALANCE_SNAPSHOTS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance_snapshots",
+    "balance_snapshots__checkpoints",
+    "balance_snapshots__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Total supply history, stored under the single key `TOTAL_SUPPLY_KEY`
+pub const TOTAL_SUPPLY_SNAPSHOTS: SnapshotMap<&str, Uint128> = SnapshotMap::new(
+    "total_supply_snapshots",
+    "total_supply_snapshots__checkpoints",
+    "total_supply_snapshots__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_SUPPLY_KEY: &str = "total_supply";
+
+/// Addresses voting on behalf of their delegators, accounts absent here vote for themselves
+pub const DELEGATES: Map<&Addr, Addr> = Map::new("delegates");
+/// Sum of the balances delegated to each address, with its history
+pub const VOTING_POWER: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Tokens staked by each holder, held by the contract itself
+pub const STAKES: Map<&Addr, Uint128> = Map::new("stakes");
+/// Sum of `STAKES`, the part of the contract's own balance that earns rewards
+pub const TOTAL_STAKED: Item<Uint128> = Item::new("total_staked");
+/// Unstaked tokens waiting for the unbonding period to pass
+pub const CLAIMS: Claims = Claims::new("claims");
+pub const STAKING_CONFIG: Item<StakingConfig> = Item::new("staking_config");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct StakingConfig {
+    /// time (in seconds) unstaked tokens stay locked before they can be claimed
+    pub unbonding_period: u64,
+    /// rate limit added per staked token
+    pub rate_limit_boost: Decimal,
+}
+
+/// Minters besides the cw20-base one, each bounded by its own cumulative cap
+pub const MINTERS: Map<&Addr, MinterAllowance> = Map::new("minters");
+/// Most tokens that can ever exist, whoever mints them, unset means uncapped. Kept apart
+/// from the cw20 `MinterData` so it outlives the minter.
+pub const SUPPLY_CAP: Item<Uint128> = Item::new("supply_cap");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinterAllowance {
+    /// most the minter can mint in total, unset means only the global cap applies
+    pub cap: Option<Uint128>,
+    pub minted: Uint128,
+}
+
+/// Fee charged on transfers and sends, unset means no fee
+pub const TRANSFER_FEE: Item<TransferFeeConfig> = Item::new("transfer_fee");
+/// Addresses whose transfers, sent or received, are never charged a fee or burn tax
+pub const FEE_EXEMPTIONS: Map<&Addr, bool> = Map::new("fee_exemptions");
+/// Share of every transfer and send burned, in basis points
+pub const BURN_RATE: Item<u16> = Item::new("burn_rate");
+/// Total destroyed by the burn tax
+pub const TAX_BURNED: Item<Uint128> = Item::new("tax_burned");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferFeeConfig {
+    /// fee in basis points of the transferred amount, paid by the sender
+    pub bps: u16,
+    pub collector: Addr,
+}
+
+/// Number of the latest airdrop stage, stages are numbered from 1
+pub const AIRDROP_LATEST_STAGE: Item<u8> = Item::new("airdrop_latest_stage");
+pub const AIRDROP_STAGES: Map<U8Key, AirdropStage> = Map::new("airdrop_stages");
+/// Addresses that claimed their airdrop, by stage
+pub const AIRDROP_CLAIMS: Map<(&Addr, U8Key), bool> = Map::new("airdrop_claims");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AirdropStage {
+    /// hex encoded sha256 merkle root of the (address, amount) leaves
+    pub merkle_root: String,
+    /// most that can be minted through claims of this stage
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+}
+
+/// Native denom rewards are distributed and claimed in
+pub const REWARD_DENOM: Item<String> = Item::new("reward_denom");
+/// Rewards distributed per token held, summed over every distribution
+pub const REWARD_INDEX: Item<Decimal> = Item::new("reward_index");
+/// Rewards of each holder, settled up to `index` whenever its balance changes
+pub const USER_REWARDS: Map<&Addr, UserRewards> = Map::new("user_rewards");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct UserRewards {
+    /// reward index at the last settlement
+    pub index: Decimal,
+    /// rewards settled but not claimed yet
+    pub pending: Uint128,
+}
+
+/// Accounts notified through a `TransferHook` message whenever they receive a transfer
+pub const TRANSFER_HOOKS: Map<&Addr, bool> = Map::new("transfer_hooks");
+
+/// Time (in seconds) until which holders locked their own account
+pub const SELF_LOCKS: Map<&Addr, u64> = Map::new("self_locks");
+/// Address allowed to lift a holder's self lock early
+pub const RECOVERY_ADDRESSES: Map<&Addr, Addr> = Map::new("recovery_addresses");
+
+/// Accounts that can neither send nor receive tokens
+pub const BLACKLIST: Map<&Addr, bool> = Map::new("blacklist");
+
+/// Vesting schedules locking part of an account's balance
+pub const VESTING: Map<&Addr, VestingSchedule> = Map::new("vesting");
+
+/// Vests `amount` linearly from `start_time` to `end_time`, nothing being vested before
+/// `cliff_time`. A pure cliff schedule has `cliff_time == end_time`. Times are in seconds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[cfg_attr(feature = "strict", serde(deny_unknown_fields))]
+pub struct VestingSchedule {
+    pub amount: Uint128,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FrozenTranche {
+    pub amount: Uint128,
+    /// block time (in seconds) from which the amount is spendable again
+    pub unlock_at: u64,
+}
+
+/// DEX routers allowed to move tokens on behalf of opted-in holders
+pub const ROUTERS: Map<&Addr, RouterConfig> = Map::new("routers");
+/// What each holder still lets each router move, keyed by (holder, router). Opt-ins made
+/// before 1.6.0 had no amount and were kept under "router_opt_ins", which is no longer read.
+pub const ROUTER_ALLOWANCES: Map<(&Addr, &Addr), Uint128> = Map::new("router_allowances");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouterConfig {
+    /// maximum amount the router can move per transaction
+    pub max_per_tx: Uint128,
+    pub expiry: Expiration,
+}
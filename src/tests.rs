@@ -1,14 +1,44 @@
 use crate::{
-    contract::{execute, instantiate, query_frozen_balance},
+    contract::{
+        execute, instantiate, migrate, query, query_account_stats, query_airdrop_stage,
+        query_all_frozen_balances, query_attestation, query_authorization_state, query_balance_at,
+        query_burn_stats, query_capabilities, query_circulating_supply, query_claims,
+        query_delegation, query_frozen_balance, query_frozen_schedule, query_is_blacklisted,
+        query_is_claimed, query_is_frozen, query_is_whitelisted, query_minters,
+        query_pending_actions, query_pending_rewards, query_permit_nonce, query_policy_contract,
+        query_risk_snapshot, query_roles, query_router, query_router_opt_in, query_self_lock,
+        query_spendable_balance, query_staked, query_supply_breakdown, query_token_hooks,
+        query_top_holders, query_total_supply_at, query_transfer_fee, query_transfer_memos,
+        query_transfer_policy, query_vesting_info, query_voting_power, query_voting_power_at,
+        reply, sudo,
+    },
     error::*,
     msg::*,
+    signature::pubkey_to_address,
+    state::*,
 };
 use cosmwasm_std::{
-    testing::{mock_dependencies, mock_env, mock_info},
-    Binary, Coin, CosmosMsg, Deps, DepsMut, SubMsg, Uint128, WasmMsg,
+    coins, from_binary,
+    testing::{mock_dependencies, mock_env, mock_info, MockApi, MockStorage},
+    to_binary, to_vec, Addr, BankMsg, Binary, Coin, ContractResult, CosmosMsg, Decimal, Deps,
+    DepsMut, Event, Order, OwnedDeps, Querier, QuerierResult, Reply, StdError, Storage, SubMsg,
+    SubMsgExecutionResponse, SystemError, SystemResult, Uint128, WasmMsg,
 };
-use cw20::{Cw20Coin, Cw20ReceiveMsg, MinterResponse, TokenInfoResponse};
-use cw20_base::contract::{query_balance, query_token_info};
+use cw0::Expiration;
+use cw20::{
+    AllAccountsResponse, AllAllowancesResponse, Cw20Coin, Cw20ReceiveMsg, EmbeddedLogo, Logo,
+    LogoInfo, MarketingInfoResponse, MinterResponse, TokenInfoResponse,
+};
+use cw20_base::{
+    allowances::query_allowance,
+    contract::{query_balance, query_download_logo, query_marketing_info, query_token_info},
+    msg::InstantiateMarketingInfo,
+    state::{MinterData, TokenInfo, BALANCES, TOKEN_INFO},
+};
+use cw_controllers::HookError;
+use k256::ecdsa::{signature::Signer, Signature, SigningKey};
+use proptest::{collection::vec, prelude::*};
+use sha2::{Digest, Sha256};
 
 fn get_balance<T: Into<String>>(deps: Deps, address: T) -> Uint128 {
     query_balance(deps, address.into()).unwrap().balance
@@ -48,6 +78,10 @@ fn do_instantiate(
             amount: frozen_amount,
         }],
         bal_cap,
+        vesting: vec![],
+        marketing: None,
+        initial_whitelist: vec![],
+        initial_burnlist: vec![],
     };
     let info = mock_info("creator", &[]);
     let env = mock_env();
@@ -55,6 +89,7 @@ fn do_instantiate(
 }
 
 // this will set up the instantiation
+#[allow(clippy::too_many_arguments)]
 fn do_instantiate_with_minter(
     deps: DepsMut,
     addr1: String,
@@ -86,6 +121,10 @@ fn do_instantiate_with_minter(
             amount: frozen_amount,
         }],
         bal_cap,
+        vesting: vec![],
+        marketing: None,
+        initial_whitelist: vec![],
+        initial_burnlist: vec![],
     };
     let info = mock_info("creator", &[]);
     let env = mock_env();
@@ -110,7 +149,52 @@ fn test_basic() {
         mint: None,
         frozen_balances: vec![],
         bal_cap: Uint128::from(5000000000000u128),
+        vesting: vec![],
+        marketing: None,
+        initial_whitelist: vec![],
+        initial_burnlist: vec![],
     };
+
+    // token info and the initial supply are validated
+    let invalid = [
+        (
+            Instantiate {
+                name: "C".to_string(),
+                ..instantiate_msg.clone()
+            },
+            ContractError::InvalidName {},
+        ),
+        (
+            Instantiate {
+                symbol: "CASH1".to_string(),
+                ..instantiate_msg.clone()
+            },
+            ContractError::InvalidSymbol {},
+        ),
+        (
+            Instantiate {
+                decimals: 19,
+                ..instantiate_msg.clone()
+            },
+            ContractError::InvalidDecimals {},
+        ),
+        (
+            Instantiate {
+                mint: Some(MinterResponse {
+                    minter: String::from("minter"),
+                    cap: Some(amount - Uint128::new(1)),
+                }),
+                ..instantiate_msg.clone()
+            },
+            ContractError::InitialSupplyExceedsCap {},
+        ),
+    ];
+    for (msg, expected) in invalid {
+        let info = mock_info("creator", &[]);
+        let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, expected);
+    }
+
     let info = mock_info("creator", &[]);
     let env = mock_env();
     let res = instantiate(deps.as_mut(), env, info, instantiate_msg).unwrap();
@@ -178,6 +262,10 @@ fn test_mint() {
     };
     let _ = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_eq!(get_balance(deps.as_ref(), addr2), amount2 + mint_amount);
+
+    // minting is advertised once a minter is configured
+    let res = query_capabilities(deps.as_ref()).unwrap();
+    assert!(res.extensions.contains(&String::from("mintable")));
 }
 
 #[test]
@@ -218,6 +306,16 @@ fn test_transfer() {
     assert_eq!(get_balance(deps.as_ref(), addr2.clone()), amount2);
     assert_eq!(get_balance(deps.as_ref(), addr3.clone()), Uint128::zero());
 
+    assert_eq!(
+        query_spendable_balance(deps.as_ref(), mock_env(), addr1.clone()).unwrap(),
+        SpendableBalanceResponse {
+            balance: amount1,
+            frozen: frozen_amount,
+            spendable: amount1 - frozen_amount,
+            cap_headroom: bal_cap - amount1,
+        }
+    );
+
     // cannot transfer all amount as some part of it frozen
     let info = mock_info(addr1.as_ref(), &[]);
     let env = mock_env();
@@ -319,17 +417,108 @@ fn test_send() {
     // and this is how it must be wrapped for the vm to process it
     assert_eq!(
         res.messages[0],
-        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: contract.clone(),
-            msg: binary_msg,
-            funds: vec![],
-        }))
+        SubMsg::reply_always(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract.clone(),
+                msg: binary_msg,
+                funds: vec![],
+            }),
+            0
+        )
     );
 
     // ensure balance is properly transferred
     let remainder = amount2.checked_sub(trans_amount).unwrap();
-    assert_eq!(get_balance(deps.as_ref(), addr2), remainder);
-    assert_eq!(get_balance(deps.as_ref(), contract), trans_amount);
+    assert_eq!(get_balance(deps.as_ref(), addr2.clone()), remainder);
+    assert_eq!(get_balance(deps.as_ref(), contract.clone()), trans_amount);
+
+    // a successful receive leaves the balances as they are
+    let ok = Reply {
+        id: 0,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), ok).unwrap();
+    assert!(res.events.is_empty());
+    assert_eq!(get_balance(deps.as_ref(), contract.clone()), trans_amount);
+
+    // a failed receive refunds the sender
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Send {
+        contract: contract.clone(),
+        amount: trans_amount,
+        msg: Binary::default(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages[0].id, 1);
+
+    let failed = Reply {
+        id: 1,
+        result: ContractResult::Err(String::from("boom")),
+    };
+    let res = reply(deps.as_mut(), mock_env(), failed.clone()).unwrap();
+    assert_eq!(res.events[0].ty, "send_failed");
+    assert_eq!(get_balance(deps.as_ref(), addr2.clone()), remainder);
+    assert_eq!(get_balance(deps.as_ref(), contract.clone()), trans_amount);
+
+    // every send is replied to once
+    let _ = reply(deps.as_mut(), mock_env(), failed).unwrap_err();
+
+    // a failed receive also refunds the fee and the burn tax and frees the rate limit
+    let creator = mock_info("creator", &[]);
+    let collector = String::from("collector");
+    let msgs = vec![
+        Execute::SetTransferFee {
+            bps: 100,
+            collector: collector.clone(),
+        },
+        Execute::SetBurnRate { bps: 100 },
+        Execute::SetRateLimit {
+            limit: Some(Uint128::new(1000)),
+        },
+    ];
+    for msg in msgs {
+        let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    }
+    let total_supply = query_token_info(deps.as_ref()).unwrap().total_supply;
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Send {
+        contract: contract.clone(),
+        amount: Uint128::new(1000),
+        msg: Binary::default(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        get_balance(deps.as_ref(), collector.clone()),
+        Uint128::new(10)
+    );
+    let failed = Reply {
+        id: 2,
+        result: ContractResult::Err(String::from("boom")),
+    };
+    let res = reply(deps.as_mut(), mock_env(), failed).unwrap();
+    assert!(res.events[0]
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "burned" && attr.value == "10"));
+    assert_eq!(get_balance(deps.as_ref(), addr2.clone()), remainder);
+    assert_eq!(get_balance(deps.as_ref(), contract.clone()), trans_amount);
+    assert_eq!(get_balance(deps.as_ref(), collector), Uint128::zero());
+    assert_eq!(
+        query_token_info(deps.as_ref()).unwrap().total_supply,
+        total_supply
+    );
+    assert_eq!(
+        query_burn_stats(deps.as_ref()).unwrap().total_burned,
+        Uint128::zero()
+    );
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        get_balance(deps.as_ref(), contract),
+        trans_amount + Uint128::new(980)
+    );
 }
 
 #[test]
@@ -399,9 +588,16 @@ fn test_update_frozen_list() {
         frozen_amount
     );
 
-    // adding frozen balance
+    // only compliance can update the frozen list
     let info = mock_info(addr2.as_ref(), &[]);
     let env = mock_env();
+    let msg = Execute::UpdateFrozenList(UpdateType::Discard(addr1.clone()));
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // adding frozen balance
+    let info = mock_info("creator", &[]);
+    let env = mock_env();
     let msg = Execute::UpdateFrozenList(UpdateType::Add(Cw20Coin {
         address: addr1.clone(),
         amount: frozen_amount,
@@ -415,7 +611,7 @@ fn test_update_frozen_list() {
     );
 
     // subtract frozen balance
-    let info = mock_info(addr2.as_ref(), &[]);
+    let info = mock_info("creator", &[]);
     let env = mock_env();
     let msg = Execute::UpdateFrozenList(UpdateType::Sub(Cw20Coin {
         address: addr1.clone(),
@@ -430,7 +626,7 @@ fn test_update_frozen_list() {
     );
 
     // discard frozen balance
-    let info = mock_info(addr2.as_ref(), &[]);
+    let info = mock_info("creator", &[]);
     let env = mock_env();
     let msg = Execute::UpdateFrozenList(UpdateType::Discard(addr1.clone()));
     let _ = execute(deps.as_mut(), env, info, msg).unwrap();
@@ -438,3 +634,4411 @@ fn test_update_frozen_list() {
     // frozen balance after subtraction
     assert_eq!(get_frozen_balance(deps.as_ref(), addr1), Uint128::zero());
 }
+
+#[test]
+fn test_all_frozen_balances() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let frozen_amount = Uint128::from(1000u128);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::from(2000u128),
+        addr2.clone(),
+        Uint128::from(2600u128),
+        frozen_amount,
+        Uint128::from(3000u128),
+    );
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::UpdateFrozenList(UpdateType::Add(Cw20Coin {
+        address: addr2.clone(),
+        amount: frozen_amount,
+    }));
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = query_all_frozen_balances(deps.as_ref(), None, Some(1)).unwrap();
+    assert_eq!(
+        res.frozen_balances,
+        vec![Cw20Coin {
+            address: addr1.clone(),
+            amount: frozen_amount,
+        }]
+    );
+
+    let res = query_all_frozen_balances(deps.as_ref(), Some(addr1), None).unwrap();
+    assert_eq!(
+        res.frozen_balances,
+        vec![Cw20Coin {
+            address: addr2,
+            amount: frozen_amount,
+        }]
+    );
+}
+
+#[test]
+fn test_rate_limit() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::zero();
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    let bal_cap = Uint128::from(3000u128);
+    let limit = Uint128::from(700u128);
+    let trans_amount = Uint128::from(400u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        bal_cap,
+    );
+
+    // only admin can set the rate limit
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::SetRateLimit { limit: Some(limit) };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::SetRateLimit { limit: Some(limit) };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // first transfer within the limit
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: trans_amount,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // second transfer exceeds the limit within the same window
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: trans_amount,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::RateLimitExceeded {
+            retry_after: mock_env().block.time.seconds() + RATE_LIMIT_WINDOW
+        }
+    );
+
+    // window rolls over after 24h
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(RATE_LIMIT_WINDOW);
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: trans_amount,
+    };
+    let _ = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // exempted addresses are not limited
+    let info = mock_info("creator", &[]);
+    let msg = Execute::SetRateLimitExemption {
+        address: addr2.clone(),
+        exempt: true,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: Uint128::from(1000u128),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        get_balance(deps.as_ref(), addr3),
+        trans_amount + trans_amount + Uint128::from(1000u128)
+    );
+}
+
+#[test]
+fn test_burn_address() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::zero();
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let sink = String::from("sink0000");
+    let bal_cap = Uint128::from(3000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        bal_cap,
+    );
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::SetBurnAddress {
+        address: sink.clone(),
+        burn: true,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // burn addresses are not bound by the balance cap
+    for (sender, amount) in [(addr1, amount1), (addr2, amount2)] {
+        let info = mock_info(sender.as_ref(), &[]);
+        let msg = Execute::Transfer {
+            recipient: sink.clone(),
+            amount,
+        };
+        let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+    assert_eq!(get_balance(deps.as_ref(), &sink), amount1 + amount2);
+
+    assert_eq!(
+        query_circulating_supply(deps.as_ref()).unwrap(),
+        CirculatingSupplyResponse {
+            total_supply: amount1 + amount2,
+            burned: amount1 + amount2,
+            circulating_supply: Uint128::zero(),
+        }
+    );
+    // the sink holds every token, yet it is no holder
+    assert!(query_top_holders(deps.as_ref(), None, None)
+        .unwrap()
+        .holders
+        .is_empty());
+
+    // unregistering it makes it a holder again
+    let info = mock_info("creator", &[]);
+    let msg = Execute::SetBurnAddress {
+        address: sink.clone(),
+        burn: false,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        query_top_holders(deps.as_ref(), None, None)
+            .unwrap()
+            .holders,
+        vec![Cw20Coin {
+            address: sink.clone(),
+            amount: amount1 + amount2,
+        }]
+    );
+    // and registering an existing holder takes it out
+    let msg = Execute::SetBurnAddress {
+        address: sink,
+        burn: true,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(query_top_holders(deps.as_ref(), None, None)
+        .unwrap()
+        .holders
+        .is_empty());
+}
+
+#[cfg(feature = "strict")]
+#[test]
+fn test_strict_messages() {
+    use cosmwasm_std::from_slice;
+
+    let msg: Execute =
+        from_slice(br#"{"transfer":{"recipient":"addr0001","amount":"10"}}"#).unwrap();
+    assert_eq!(
+        msg,
+        Execute::Transfer {
+            recipient: String::from("addr0001"),
+            amount: Uint128::from(10u128),
+        }
+    );
+
+    // unknown fields are rejected rather than silently dropped
+    from_slice::<Execute>(br#"{"transfer":{"recipient":"addr0001","amount":"10","memo":"x"}}"#)
+        .unwrap_err();
+    from_slice::<Query>(br#"{"balance":{"address":"addr0001","height":1}}"#).unwrap_err();
+    from_slice::<Execute>(br#"{"schedule":{"action":{"set_burn_rate":{"bps":10,"x":1}}}}"#)
+        .unwrap_err();
+
+    // nested structs are held to it as well
+    let attestation =
+        br#"{"attest_batch":{"attestations":[{"address":"addr0001","level":1,"expires":10}]}}"#;
+    from_slice::<Execute>(attestation).unwrap();
+    let attestation = br#"{"attest_batch":{"attestations":[{"address":"addr0001","level":1,"expires":10,"x":1}]}}"#;
+    from_slice::<Execute>(attestation).unwrap_err();
+    let vesting = br#"{"create_vesting":{"address":"addr0001","schedule":{"amount":"10","start_time":1,"cliff_time":2,"end_time":3,"x":1}}}"#;
+    from_slice::<Execute>(vesting).unwrap_err();
+}
+
+#[test]
+fn test_migrate() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let bal_cap = Uint128::from(3000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::from(2000u128),
+        addr2,
+        Uint128::from(2600u128),
+        Uint128::zero(),
+        bal_cap,
+    );
+
+    // a 1.0.0 deployment has no admin, one must be supplied
+    cw2::set_contract_version(deps.as_mut().storage, "token_contract", "1.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::AdminRequired {
+            version: String::from("1.0.0")
+        }
+    );
+
+    // the admin ends up with every role
+    let msg = MigrateMsg {
+        admin: Some(addr1.clone()),
+    };
+    let _ = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        query_roles(deps.as_ref(), addr1.clone()).unwrap().roles,
+        Role::ALL.to_vec()
+    );
+    assert_eq!(ADMIN.may_load(deps.as_ref().storage).unwrap(), None);
+    assert_eq!(
+        cw2::get_contract_version(deps.as_ref().storage)
+            .unwrap()
+            .version,
+        "1.6.0"
+    );
+    // the balance history starts at the migration height
+    let height = mock_env().block.height;
+    assert_eq!(
+        query_balance_at(deps.as_ref(), addr1.clone(), height + 1).unwrap(),
+        BalanceAtResponse {
+            balance: Uint128::from(2000u128),
+            height: height + 1,
+        }
+    );
+
+    // migrating the current version is a no-op
+    let _ = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+
+    // cannot migrate from another contract or an unknown version
+    cw2::set_contract_version(deps.as_mut().storage, "token_contract", "0.9.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::UnsupportedVersion {
+            version: String::from("0.9.0")
+        }
+    );
+
+    cw2::set_contract_version(deps.as_mut().storage, "other_contract", "1.1.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::CannotMigrate {
+            previous_contract: String::from("other_contract")
+        }
+    );
+}
+
+#[test]
+fn test_migrate_seeds_balance_history() {
+    // a 1.2.0 deployment holds balances but no history, stats, holder index or supply cap
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = Addr::unchecked("addr0001");
+    let addr2 = Addr::unchecked("addr0002");
+    let token_info = TokenInfo {
+        name: String::from("Auto Gen"),
+        symbol: String::from("AUTO"),
+        decimals: 3,
+        total_supply: Uint128::new(3000),
+        mint: Some(MinterData {
+            minter: Addr::unchecked("minter"),
+            cap: Some(Uint128::new(8000)),
+        }),
+    };
+    TOKEN_INFO.save(deps.as_mut().storage, &token_info).unwrap();
+    BALANCES
+        .save(deps.as_mut().storage, &addr1, &Uint128::new(2000))
+        .unwrap();
+    BALANCES
+        .save(deps.as_mut().storage, &addr2, &Uint128::new(1000))
+        .unwrap();
+    BALANCE_CAP
+        .save(deps.as_mut().storage, &Uint128::new(5000))
+        .unwrap();
+    cw2::set_contract_version(deps.as_mut().storage, "token_contract", "1.2.0").unwrap();
+    let _ = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+    // the supply cap no longer depends on the minter
+    assert_eq!(
+        SUPPLY_CAP.load(deps.as_ref().storage).unwrap(),
+        Uint128::new(8000)
+    );
+    assert!(
+        !query_top_holders(deps.as_ref(), None, None)
+            .unwrap()
+            .complete
+    );
+
+    // accounts the backfill has not reached yet cannot move tokens
+    let transfer = Execute::Transfer {
+        recipient: addr2.to_string(),
+        amount: Uint128::new(100),
+    };
+    let info = mock_info(addr1.as_ref(), &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MigrationPending {
+            address: addr1.to_string()
+        }
+    );
+
+    // the backfill goes through the accounts a page at a time
+    let step = Execute::MigrateHolders { limit: Some(1) };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), step.clone()).unwrap();
+    assert_eq!(res.attributes[2].value, "false");
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MigrationPending {
+            address: addr2.to_string()
+        }
+    );
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), step.clone()).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), step.clone()).unwrap();
+    assert_eq!(res.attributes[2].value, "true");
+    let err = execute(deps.as_mut(), mock_env(), info, step).unwrap_err();
+    assert_eq!(err, ContractError::NoBackfillPending {});
+    let res = query_top_holders(deps.as_ref(), None, None).unwrap();
+    assert!(res.complete);
+    assert_eq!(
+        res.holders,
+        vec![
+            Cw20Coin {
+                address: addr1.to_string(),
+                amount: Uint128::new(2000),
+            },
+            Cw20Coin {
+                address: addr2.to_string(),
+                amount: Uint128::new(1000),
+            }
+        ]
+    );
+
+    // the history starts at the migration height, with nobody having moved any token
+    let height = mock_env().block.height;
+    assert_eq!(
+        query_balance_at(deps.as_ref(), addr1.to_string(), height + 1)
+            .unwrap()
+            .balance,
+        Uint128::new(2000)
+    );
+    assert_eq!(
+        query_voting_power(deps.as_ref(), addr2.to_string())
+            .unwrap()
+            .power,
+        Uint128::new(1000)
+    );
+    assert_eq!(
+        query_account_stats(deps.as_ref(), addr1.to_string()).unwrap(),
+        AccountStatsResponse {
+            tx_count: 0,
+            total_sent: Uint128::zero(),
+            total_received: Uint128::zero(),
+            last_activity_height: 0,
+        }
+    );
+    let breakdown = query_supply_breakdown(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(breakdown.total_minted, Uint128::new(3000));
+    assert_eq!(breakdown.total_burned, Uint128::zero());
+}
+
+#[test]
+fn test_enumerable_queries() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    let allow_amount = Uint128::from(100u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::from(2000u128),
+        addr2.clone(),
+        Uint128::from(2600u128),
+        Uint128::zero(),
+        Uint128::from(3000u128),
+    );
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::IncreaseAllowance {
+        spender: addr3.clone(),
+        amount: allow_amount,
+        expires: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // paginating through all accounts
+    let msg = Query::AllAccounts {
+        start_after: None,
+        limit: Some(1),
+    };
+    let res: AllAccountsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.accounts, vec![addr1.clone()]);
+
+    let msg = Query::AllAccounts {
+        start_after: Some(addr1.clone()),
+        limit: None,
+    };
+    let res: AllAccountsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.accounts, vec![addr2]);
+
+    let msg = Query::AllAllowances {
+        owner: addr1,
+        start_after: None,
+        limit: None,
+    };
+    let res: AllAllowancesResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.allowances.len(), 1);
+    assert_eq!(res.allowances[0].spender, addr3);
+    assert_eq!(res.allowances[0].allowance, allow_amount);
+
+    // no logo has been uploaded
+    let _ = query(deps.as_ref(), mock_env(), Query::DownloadLogo {}).unwrap_err();
+
+    let res = query_capabilities(deps.as_ref()).unwrap();
+    assert!(res.extensions.contains(&String::from("enumerable")));
+    assert!(!res.extensions.contains(&String::from("mintable")));
+}
+
+#[test]
+fn test_transfer_batch() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::from(1000u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    let addr4 = String::from("addr0004");
+    let trans_amount = Uint128::from(400u128);
+    let bal_cap = Uint128::from(3000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        bal_cap,
+    );
+
+    // recipient over the balance cap
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::TransferBatch {
+        transfers: vec![Cw20Coin {
+            address: addr2.clone(),
+            amount: trans_amount + trans_amount,
+        }],
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    // valid batch
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::TransferBatch {
+        transfers: vec![
+            Cw20Coin {
+                address: addr3.clone(),
+                amount: trans_amount,
+            },
+            Cw20Coin {
+                address: addr4.clone(),
+                amount: trans_amount,
+            },
+        ],
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        get_balance(deps.as_ref(), addr1.clone()),
+        amount1 - trans_amount - trans_amount
+    );
+    assert_eq!(get_balance(deps.as_ref(), addr3.clone()), trans_amount);
+    assert_eq!(get_balance(deps.as_ref(), addr4.clone()), trans_amount);
+
+    // remaining spendable balance does not cover the batch
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::TransferBatch {
+        transfers: vec![
+            Cw20Coin {
+                address: addr3,
+                amount: amount1 - frozen_amount,
+            },
+            Cw20Coin {
+                address: addr4,
+                amount: trans_amount,
+            },
+        ],
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::BalanceFrozen {});
+}
+
+#[test]
+fn test_mint_batch() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(500u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(250u128);
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    let bal_cap = Uint128::from(1000u128);
+    let mint_amount = Uint128::from(400u128);
+    let minter = String::from("minter");
+    let cap = Some(Uint128::from(2000u128));
+
+    do_instantiate_with_minter(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        Uint128::zero(),
+        bal_cap,
+        minter.clone(),
+        cap,
+    );
+
+    // only the minter can mint
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::MintBatch {
+        recipients: vec![Cw20Coin {
+            address: addr3.clone(),
+            amount: mint_amount,
+        }],
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // balance cap exceeded for one of the recipients
+    let info = mock_info(minter.as_ref(), &[]);
+    let msg = Execute::MintBatch {
+        recipients: vec![Cw20Coin {
+            address: addr1.clone(),
+            amount: bal_cap,
+        }],
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    // valid batch, including an address without any balance yet
+    let info = mock_info(minter.as_ref(), &[]);
+    let msg = Execute::MintBatch {
+        recipients: vec![
+            Cw20Coin {
+                address: addr1.clone(),
+                amount: mint_amount,
+            },
+            Cw20Coin {
+                address: addr3.clone(),
+                amount: mint_amount,
+            },
+        ],
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), addr1), amount1 + mint_amount);
+    assert_eq!(get_balance(deps.as_ref(), addr3.clone()), mint_amount);
+
+    // mint cap exceeded
+    let info = mock_info(minter.as_ref(), &[]);
+    let msg = Execute::MintBatch {
+        recipients: vec![Cw20Coin {
+            address: addr3,
+            amount: bal_cap - mint_amount,
+        }],
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+}
+
+#[cfg(feature = "simulation")]
+#[test]
+fn test_simulation_matches_contract() {
+    use crate::simulation::Ledger;
+
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::from(1000u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    let bal_cap = Uint128::from(3000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        bal_cap,
+    );
+    let mut ledger = Ledger::load(deps.as_ref()).unwrap();
+
+    let env = mock_env();
+    let now = env.block.time.seconds();
+    let transfers = vec![
+        (addr1.clone(), addr3.clone(), Uint128::from(500u128)),
+        (addr1.clone(), addr3.clone(), Uint128::from(600u128)),
+        (addr2.clone(), addr3.clone(), Uint128::from(2600u128)),
+        (addr2.clone(), addr1.clone(), Uint128::zero()),
+        (addr2.clone(), addr1.clone(), Uint128::from(1000u128)),
+    ];
+    for (sender, recipient, amount) in transfers {
+        let simulated = ledger.transfer(&sender, &recipient, amount, now);
+        let info = mock_info(sender.as_ref(), &[]);
+        let msg = Execute::Transfer { recipient, amount };
+        let executed = execute(deps.as_mut(), env.clone(), info, msg).map(|_| ());
+        assert_eq!(simulated, executed);
+    }
+
+    assert_eq!(ledger, Ledger::load(deps.as_ref()).unwrap());
+}
+
+#[cfg(feature = "migration-preview")]
+#[test]
+fn test_migration_preview() {
+    use crate::contract::query_migration_preview;
+
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+
+    do_instantiate(
+        deps.as_mut(),
+        String::from("addr0001"),
+        Uint128::from(2000u128),
+        String::from("addr0002"),
+        Uint128::from(2600u128),
+        Uint128::zero(),
+        Uint128::from(3000u128),
+    );
+
+    let no_admin = MigrateMsg { admin: None };
+    let with_admin = MigrateMsg {
+        admin: Some(String::from("addr0001")),
+    };
+
+    // nothing to rewrite when already on the current version
+    let res =
+        query_migration_preview(deps.as_ref(), String::from("1.6.0"), no_admin.clone()).unwrap();
+    assert!(res.namespaces.is_empty());
+
+    // a 1.0.0 deployment cannot migrate without an admin, like migrate itself
+    cw2::set_contract_version(deps.as_mut().storage, "token_contract", "1.0.0").unwrap();
+    let err = query_migration_preview(deps.as_ref(), String::from("1.6.0"), no_admin.clone())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(
+            ContractError::AdminRequired {
+                version: String::from("1.0.0")
+            }
+            .to_string()
+        )
+    );
+
+    let res = query_migration_preview(deps.as_ref(), String::from("1.6.0"), with_admin).unwrap();
+    assert_eq!(res.from_version, "1.0.0");
+    assert_eq!(
+        res.namespaces,
+        vec![
+            NamespacePreview {
+                namespace: String::from("admin"),
+                affected_entries: 1,
+                invalid_entries: vec![],
+            },
+            NamespacePreview {
+                namespace: String::from("roles"),
+                affected_entries: 6,
+                invalid_entries: vec![],
+            },
+            NamespacePreview {
+                namespace: String::from("total_supply_snapshots"),
+                affected_entries: 1,
+                invalid_entries: vec![],
+            },
+            NamespacePreview {
+                namespace: String::from("backfill"),
+                affected_entries: 2,
+                invalid_entries: vec![],
+            },
+            NamespacePreview {
+                namespace: String::from("supply_stats"),
+                affected_entries: 1,
+                invalid_entries: vec![],
+            },
+            NamespacePreview {
+                namespace: String::from("supply_cap"),
+                affected_entries: 0,
+                invalid_entries: vec![],
+            },
+            NamespacePreview {
+                namespace: String::from("total_staked"),
+                affected_entries: 1,
+                invalid_entries: vec![],
+            },
+        ]
+    );
+
+    // a balance stored under a key that is not an address would stop the backfill
+    let mut key = vec![0u8, 7];
+    key.extend_from_slice(b"balance");
+    key.push(0xff);
+    deps.as_mut()
+        .storage
+        .set(&key, &to_vec(&Uint128::new(5)).unwrap());
+    cw2::set_contract_version(deps.as_mut().storage, "token_contract", "1.4.0").unwrap();
+    let res =
+        query_migration_preview(deps.as_ref(), String::from("1.6.0"), no_admin.clone()).unwrap();
+    assert_eq!(
+        res.namespaces[0],
+        NamespacePreview {
+            namespace: String::from("backfill"),
+            affected_entries: 3,
+            invalid_entries: vec![String::from("ff")],
+        }
+    );
+    let _ = migrate(deps.as_mut(), mock_env(), no_admin.clone()).unwrap();
+    let info = mock_info("addr0001", &[]);
+    let msg = Execute::MigrateHolders { limit: None };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+
+    let _ = query_migration_preview(deps.as_ref(), String::from("2.0.0"), no_admin).unwrap_err();
+}
+
+#[test]
+fn test_update_balance_cap() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let bal_cap = Uint128::from(3000u128);
+    let new_cap = Uint128::from(5000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        Uint128::zero(),
+        bal_cap,
+    );
+
+    // transfer over the current cap
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: amount1,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    // only admin can update the cap, and not to zero
+    let info = mock_info(addr1.as_ref(), &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        Execute::UpdateBalanceCap { new_cap },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        Execute::UpdateBalanceCap {
+            new_cap: Uint128::zero(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidZeroAmount {});
+
+    let info = mock_info("creator", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        Execute::UpdateBalanceCap { new_cap },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1].value, bal_cap.to_string());
+    assert_eq!(res.attributes[2].value, new_cap.to_string());
+
+    // the same transfer now fits under the cap
+    let info = mock_info(addr1.as_ref(), &[]);
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), addr2), amount1 + amount2);
+}
+
+#[test]
+fn test_router_transfer_from() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::from(1200u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    let router = String::from("router");
+    let max_per_tx = Uint128::from(500u128);
+    let bal_cap = Uint128::from(3000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        bal_cap,
+    );
+
+    let transfer_msg = |amount: Uint128| Execute::RouterTransferFrom {
+        owner: addr1.clone(),
+        recipient: addr3.clone(),
+        amount,
+    };
+
+    // unregistered routers cannot move funds
+    let info = mock_info(router.as_ref(), &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, transfer_msg(max_per_tx)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::AuthorizeRouter {
+        router: router.clone(),
+        max_per_tx,
+        expiry: Expiration::AtHeight(mock_env().block.height + 10),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query_router(deps.as_ref(), router.clone()).unwrap();
+    assert_eq!(res.max_per_tx, max_per_tx);
+
+    // owner has not opted in yet
+    let info = mock_info(router.as_ref(), &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, transfer_msg(max_per_tx)).unwrap_err();
+    assert_eq!(err, ContractError::NoAllowance {});
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::OptInRouter {
+        router: router.clone(),
+        amount: Uint128::from(1000u128),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        query_router_opt_in(deps.as_ref(), addr1.clone(), router.clone()).unwrap(),
+        RouterOptInResponse {
+            opted_in: true,
+            allowance: Uint128::from(1000u128),
+        }
+    );
+
+    // over the per transaction limit
+    let info = mock_info(router.as_ref(), &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        transfer_msg(max_per_tx + Uint128::from(1u128)),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::RouterLimitExceeded {});
+
+    // valid router transfer
+    let info = mock_info(router.as_ref(), &[]);
+    let _ = execute(deps.as_mut(), mock_env(), info, transfer_msg(max_per_tx)).unwrap();
+    assert_eq!(
+        get_balance(deps.as_ref(), addr1.clone()),
+        amount1 - max_per_tx
+    );
+    assert_eq!(get_balance(deps.as_ref(), addr3.clone()), max_per_tx);
+    let allowance = |deps: Deps| {
+        query_router_opt_in(deps, addr1.clone(), router.clone())
+            .unwrap()
+            .allowance
+    };
+    assert_eq!(allowance(deps.as_ref()), Uint128::from(500u128));
+
+    // frozen balance still applies to router transfers, and leaves the allowance alone
+    let info = mock_info(router.as_ref(), &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, transfer_msg(max_per_tx)).unwrap_err();
+    assert_eq!(err, ContractError::BalanceFrozen {});
+    assert_eq!(allowance(deps.as_ref()), Uint128::from(500u128));
+
+    // the allowance runs out across transactions
+    let info = mock_info(router.as_ref(), &[]);
+    let msg = transfer_msg(Uint128::from(300u128));
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(allowance(deps.as_ref()), Uint128::from(200u128));
+    let info = mock_info(router.as_ref(), &[]);
+    let msg = transfer_msg(Uint128::from(201u128));
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::RouterAllowanceExceeded {});
+
+    // blacklisted or frozen routers cannot move funds
+    let creator = mock_info("creator", &[]);
+    for (block, unblock, expected) in [
+        (
+            Execute::BlackList {
+                address: router.clone(),
+            },
+            Execute::RemoveBlackList {
+                address: router.clone(),
+            },
+            ContractError::Blacklisted {},
+        ),
+        (
+            Execute::FreezeAccount {
+                address: router.clone(),
+            },
+            Execute::UnfreezeAccount {
+                address: router.clone(),
+            },
+            ContractError::AccountFrozen {},
+        ),
+    ] {
+        let _ = execute(deps.as_mut(), mock_env(), creator.clone(), block).unwrap();
+        let info = mock_info(router.as_ref(), &[]);
+        let msg = transfer_msg(Uint128::from(1u128));
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, expected);
+        let _ = execute(deps.as_mut(), mock_env(), creator.clone(), unblock).unwrap();
+    }
+
+    // expired routers cannot move funds
+    let mut env = mock_env();
+    env.block.height += 10;
+    let info = mock_info(router.as_ref(), &[]);
+    let err = execute(deps.as_mut(), env, info, transfer_msg(Uint128::from(1u128))).unwrap_err();
+    assert_eq!(err, ContractError::Expired {});
+}
+
+#[test]
+fn test_send_cap() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let send_msg = Binary::from(r#"{"some":123}"#.as_bytes());
+    let bal_cap = Uint128::from(3000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        Uint128::zero(),
+        bal_cap,
+    );
+
+    // cannot send to a contract that would go over the balance cap
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Send {
+        contract: addr2.clone(),
+        amount: amount1,
+        msg: send_msg.clone(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    // same for send_from
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::IncreaseAllowance {
+        spender: addr2.clone(),
+        amount: amount1,
+        expires: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::SendFrom {
+        owner: addr1.clone(),
+        contract: addr2.clone(),
+        amount: amount1,
+        msg: send_msg,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    assert_eq!(get_balance(deps.as_ref(), addr1), amount1);
+    assert_eq!(get_balance(deps.as_ref(), addr2), amount2);
+}
+
+#[test]
+fn test_frozen_exceeds_balance() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::from(1500u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        Uint128::from(3000u128),
+    );
+
+    // freezing more than the account holds
+    let info = mock_info("creator", &[]);
+    let msg = Execute::UpdateFrozenList(UpdateType::Add(Cw20Coin {
+        address: addr1.clone(),
+        amount: frozen_amount,
+    }));
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // every outgoing path fails with a typed error instead of panicking
+    let msgs = vec![
+        Execute::Transfer {
+            recipient: addr3.clone(),
+            amount: Uint128::from(1u128),
+        },
+        Execute::Send {
+            contract: addr3.clone(),
+            amount: Uint128::from(1u128),
+            msg: Binary::default(),
+        },
+        Execute::Burn {
+            amount: Uint128::from(1u128),
+        },
+    ];
+    for msg in msgs {
+        let info = mock_info(addr1.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::BalanceFrozen {});
+    }
+
+    // *_from paths check the owner's frozen balance, not the spender's
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::IncreaseAllowance {
+        spender: addr3.clone(),
+        amount: amount1,
+        expires: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msgs = vec![
+        Execute::TransferFrom {
+            owner: addr1.clone(),
+            recipient: addr2.clone(),
+            amount: Uint128::from(1u128),
+        },
+        Execute::SendFrom {
+            owner: addr1.clone(),
+            contract: addr2.clone(),
+            amount: Uint128::from(1u128),
+            msg: Binary::default(),
+        },
+        Execute::BurnFrom {
+            owner: addr1.clone(),
+            amount: Uint128::from(1u128),
+        },
+    ];
+    for msg in msgs {
+        let info = mock_info(addr3.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::BalanceFrozen {});
+    }
+
+    // spendable balance reflects the fully frozen account
+    let res = query_spendable_balance(deps.as_ref(), mock_env(), addr1).unwrap();
+    assert_eq!(res.spendable, Uint128::zero());
+}
+
+#[test]
+fn test_freeze_account() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::zero();
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        Uint128::from(3000u128),
+    );
+
+    // only admin can freeze accounts
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::FreezeAccount {
+        address: addr1.clone(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::FreezeAccount {
+        address: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(
+        query_is_frozen(deps.as_ref(), addr1.clone())
+            .unwrap()
+            .frozen
+    );
+
+    // frozen account cannot move anything out
+    let msgs = vec![
+        Execute::Transfer {
+            recipient: addr3.clone(),
+            amount: Uint128::from(1u128),
+        },
+        Execute::Send {
+            contract: addr3.clone(),
+            amount: Uint128::from(1u128),
+            msg: Binary::default(),
+        },
+        Execute::Burn {
+            amount: Uint128::from(1u128),
+        },
+    ];
+    for msg in msgs {
+        let info = mock_info(addr1.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AccountFrozen {});
+    }
+
+    // but can still receive
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr1.clone(),
+        amount: Uint128::from(100u128),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        get_balance(deps.as_ref(), addr1.clone()),
+        amount1 + Uint128::from(100u128)
+    );
+
+    // unfreezing restores transfers
+    let info = mock_info("creator", &[]);
+    let msg = Execute::UnfreezeAccount {
+        address: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(
+        !query_is_frozen(deps.as_ref(), addr1.clone())
+            .unwrap()
+            .frozen
+    );
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: Uint128::from(100u128),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), addr3), Uint128::from(100u128));
+}
+
+#[test]
+fn test_frozen_schedule() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::from(500u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        Uint128::from(3000u128),
+    );
+
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+
+    // cannot lock until a time that has already passed
+    let info = mock_info("creator", &[]);
+    let msg = Execute::UpdateFrozenList(UpdateType::Lock {
+        address: addr1.clone(),
+        amount: Uint128::from(700u128),
+        unlock_at: now,
+    });
+    let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Expired {});
+
+    for (amount, unlock_at) in [(700u128, now + 100), (300u128, now + 200)] {
+        let info = mock_info("creator", &[]);
+        let msg = Execute::UpdateFrozenList(UpdateType::Lock {
+            address: addr1.clone(),
+            amount: Uint128::from(amount),
+            unlock_at,
+        });
+        let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+
+    // 500 manually frozen plus 1000 time-locked
+    let res = query_spendable_balance(deps.as_ref(), env.clone(), addr1.clone()).unwrap();
+    assert_eq!(res.frozen, Uint128::from(1500u128));
+    assert_eq!(res.spendable, Uint128::from(500u128));
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: Uint128::from(501u128),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::BalanceFrozen {});
+
+    // first tranche expires by itself
+    env.block.time = env.block.time.plus_seconds(100);
+    assert_eq!(
+        query_frozen_schedule(deps.as_ref(), env.clone(), addr1.clone()).unwrap(),
+        FrozenScheduleResponse {
+            tranches: vec![FrozenTranche {
+                amount: Uint128::from(300u128),
+                unlock_at: now + 200,
+            }],
+        }
+    );
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: Uint128::from(1200u128),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), addr3), Uint128::from(1200u128));
+
+    // manually frozen balance is untouched once every tranche expired
+    env.block.time = env.block.time.plus_seconds(100);
+    let res = query_spendable_balance(deps.as_ref(), env.clone(), addr1.clone()).unwrap();
+    assert_eq!(res.frozen, frozen_amount);
+    assert!(query_frozen_schedule(deps.as_ref(), env, addr1)
+        .unwrap()
+        .tranches
+        .is_empty());
+}
+
+#[test]
+fn test_vesting() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    let amount = Uint128::from(2000u128);
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+
+    // linear schedule over 1000s with a 100s cliff, set at instantiation
+    let linear = VestingSchedule {
+        amount: Uint128::from(1000u128),
+        start_time: now,
+        cliff_time: now + 100,
+        end_time: now + 1000,
+    };
+    let instantiate_msg = Instantiate {
+        name: "Bash Shell".to_string(),
+        symbol: "BASH".to_string(),
+        decimals: 6,
+        initial_balances: vec![
+            Cw20Coin {
+                address: addr1.clone(),
+                amount,
+            },
+            Cw20Coin {
+                address: addr2.clone(),
+                amount,
+            },
+        ],
+        mint: None,
+        frozen_balances: vec![],
+        bal_cap: Uint128::from(3000u128),
+        vesting: vec![VestingAccount {
+            address: addr1.clone(),
+            schedule: linear.clone(),
+        }],
+        marketing: None,
+        initial_whitelist: vec![],
+        initial_burnlist: vec![],
+    };
+    let info = mock_info("creator", &[]);
+    let _ = instantiate(deps.as_mut(), env.clone(), info, instantiate_msg).unwrap();
+
+    // invalid schedules are rejected
+    let info = mock_info("creator", &[]);
+    let msg = Execute::CreateVesting {
+        address: addr2.clone(),
+        schedule: VestingSchedule {
+            amount: Uint128::from(1000u128),
+            start_time: now + 10,
+            cliff_time: now,
+            end_time: now + 1000,
+        },
+    };
+    let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidVestingSchedule {});
+
+    // only admin can create vesting schedules
+    let cliff = VestingSchedule {
+        amount: Uint128::from(1500u128),
+        start_time: now,
+        cliff_time: now + 500,
+        end_time: now + 500,
+    };
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::CreateVesting {
+        address: addr2.clone(),
+        schedule: cliff.clone(),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::CreateVesting {
+        address: addr2.clone(),
+        schedule: cliff,
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // before the cliff everything is unvested
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: Uint128::from(1001u128),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::BalanceFrozen {});
+
+    // halfway through, half of the linear schedule is vested
+    env.block.time = env.block.time.plus_seconds(500);
+    assert_eq!(
+        query_vesting_info(deps.as_ref(), env.clone(), addr1.clone()).unwrap(),
+        VestingInfoResponse {
+            schedule: Some(linear),
+            vested: Uint128::from(500u128),
+            unvested: Uint128::from(500u128),
+        }
+    );
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: Uint128::from(1500u128),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // cliff schedule vests entirely at once
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Burn { amount };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), addr2), Uint128::zero());
+
+    // accounts without schedule report nothing
+    let res = query_vesting_info(deps.as_ref(), env, addr3).unwrap();
+    assert_eq!(res.schedule, None);
+    assert_eq!(res.unvested, Uint128::zero());
+}
+
+#[test]
+fn test_clawback() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::from(1500u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let recovery = String::from("recovery");
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        Uint128::from(3000u128),
+    );
+
+    let msg = Execute::Clawback {
+        owner: addr1.clone(),
+        recipient: recovery.clone(),
+        amount: amount1,
+    };
+
+    // only admin can claw back
+    let info = mock_info(addr2.as_ref(), &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // only from frozen accounts
+    let info = mock_info("creator", &[]);
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::AccountNotFrozen {});
+
+    let info = mock_info("creator", &[]);
+    let freeze = Execute::FreezeAccount {
+        address: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, freeze).unwrap();
+
+    // the whole balance is seized, frozen part included
+    let info = mock_info("creator", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes[0].value, "clawback");
+    assert_eq!(
+        res.events,
+        vec![Event::new("seizure")
+            .add_attribute("owner", &addr1)
+            .add_attribute("recipient", &recovery)
+            .add_attribute("amount", amount1)
+            .add_attribute("remaining", "0")
+            .add_attribute("authority", "creator")
+            .add_attribute("reason", "frozen")]
+    );
+    assert_eq!(get_balance(deps.as_ref(), addr1.clone()), Uint128::zero());
+    assert_eq!(get_balance(deps.as_ref(), recovery.clone()), amount1);
+
+    // blacklisted accounts can be seized from as well
+    let info = mock_info("creator", &[]);
+    let msg = Execute::BlackList {
+        address: addr2.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info("creator", &[]);
+    let msg = Execute::Clawback {
+        owner: addr2,
+        recipient: recovery.clone(),
+        amount: Uint128::from(100u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let seizure = &res.events[0].attributes;
+    assert_eq!(seizure[3].value, "2500");
+    assert_eq!(seizure[5].value, "blacklisted");
+
+    // cannot seize more than held
+    let info = mock_info("creator", &[]);
+    let msg = Execute::Clawback {
+        owner: addr1,
+        recipient: recovery,
+        amount: Uint128::from(1u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+}
+
+#[test]
+fn test_blacklist() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::zero();
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let minter = String::from("minter");
+
+    do_instantiate_with_minter(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        Uint128::from(3000u128),
+        minter.clone(),
+        None,
+    );
+
+    // only admin can blacklist
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::BlackList {
+        address: addr1.clone(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::BlackList {
+        address: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(
+        query_is_blacklisted(deps.as_ref(), addr1.clone())
+            .unwrap()
+            .blacklisted
+    );
+
+    // blacklisted account can neither send nor receive
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::from(1u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Blacklisted {});
+
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr1.clone(),
+        amount: Uint128::from(1u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Blacklisted {});
+
+    let info = mock_info(minter.as_ref(), &[]);
+    let msg = Execute::Mint {
+        recipient: addr1.clone(),
+        amount: Uint128::from(1u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Blacklisted {});
+
+    // nor spend allowances it was granted
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::IncreaseAllowance {
+        spender: addr1.clone(),
+        amount: amount2,
+        expires: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::TransferFrom {
+        owner: addr2.clone(),
+        recipient: String::from("addr0003"),
+        amount: Uint128::from(1u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Blacklisted {});
+
+    // but its tokens can be clawed back
+    let info = mock_info("creator", &[]);
+    let msg = Execute::Clawback {
+        owner: addr1.clone(),
+        recipient: String::from("recovery"),
+        amount: amount1,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), addr1.clone()), Uint128::zero());
+
+    // removal restores receiving
+    let info = mock_info("creator", &[]);
+    let msg = Execute::RemoveBlackList {
+        address: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr1.clone(),
+        amount: Uint128::from(1u128),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), addr1), Uint128::from(1u128));
+}
+
+#[test]
+fn test_transfer_hook() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::zero();
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let vault = String::from("vault");
+    let transfer = Uint128::from(100u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        Uint128::from(3000u128),
+    );
+
+    // no hook until the recipient registers
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: vault.clone(),
+        amount: transfer,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    let info = mock_info(vault.as_ref(), &[]);
+    let _ = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        Execute::RegisterTransferHook {},
+    )
+    .unwrap();
+
+    let hook = TransferHookMsg {
+        sender: addr1.clone(),
+        amount: transfer,
+    }
+    .into_binary()
+    .unwrap();
+    let expected = vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: vault.clone(),
+        msg: hook,
+        funds: vec![],
+    }))];
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: vault.clone(),
+        amount: transfer,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages, expected);
+
+    // hooks from batches and allowance transfers name the owner as sender
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::TransferBatch {
+        transfers: vec![Cw20Coin {
+            address: vault.clone(),
+            amount: transfer,
+        }],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages, expected);
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::IncreaseAllowance {
+        spender: addr2.clone(),
+        amount: transfer,
+        expires: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::TransferFrom {
+        owner: addr1.clone(),
+        recipient: vault.clone(),
+        amount: transfer,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages, expected);
+
+    // unregistering stops the hooks
+    let info = mock_info(vault.as_ref(), &[]);
+    let _ = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        Execute::UnregisterTransferHook {},
+    )
+    .unwrap();
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: vault,
+        amount: transfer,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn test_roles() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let compliance = String::from("compliance");
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::from(2000u128),
+        addr2.clone(),
+        Uint128::from(2600u128),
+        Uint128::zero(),
+        Uint128::from(3000u128),
+    );
+
+    // instantiator holds every role
+    assert_eq!(
+        query_roles(deps.as_ref(), String::from("creator"))
+            .unwrap()
+            .roles,
+        Role::ALL.to_vec()
+    );
+
+    // only admins can grant roles
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::GrantRole {
+        address: compliance.clone(),
+        role: Role::Compliance,
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info("creator", &[]);
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        query_roles(deps.as_ref(), compliance.clone())
+            .unwrap()
+            .roles,
+        vec![Role::Compliance]
+    );
+
+    // compliance can freeze but not touch admin settings
+    let info = mock_info(compliance.as_ref(), &[]);
+    let msg = Execute::FreezeAccount {
+        address: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(compliance.as_ref(), &[]);
+    let msg = Execute::UpdateBalanceCap {
+        new_cap: Uint128::from(5000u128),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // revoked roles no longer authorize
+    let info = mock_info("creator", &[]);
+    let msg = Execute::RevokeRole {
+        address: compliance.clone(),
+        role: Role::Compliance,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(query_roles(deps.as_ref(), compliance.clone())
+        .unwrap()
+        .roles
+        .is_empty());
+
+    let info = mock_info(compliance.as_ref(), &[]);
+    let msg = Execute::UnfreezeAccount { address: addr1 };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_timelock() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let bal_cap = Uint128::from(3000u128);
+    let new_cap = Uint128::from(5000u128);
+    let delay = 3600u64;
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1,
+        Uint128::from(2000u128),
+        addr2.clone(),
+        Uint128::from(2600u128),
+        Uint128::zero(),
+        bal_cap,
+    );
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::SetTimelockDelay { delay };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // sensitive actions can no longer be applied directly
+    let info = mock_info("creator", &[]);
+    let msg = Execute::UpdateBalanceCap { new_cap };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::TimelockRequired {});
+
+    // only admins can schedule
+    let action = ScheduledAction::UpdateBalanceCap { new_cap };
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Schedule {
+        action: action.clone(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let mut env = mock_env();
+    let eta = env.block.time.seconds() + delay;
+    for _ in 0..2 {
+        let info = mock_info("creator", &[]);
+        let msg = Execute::Schedule {
+            action: action.clone(),
+        };
+        let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    }
+    assert_eq!(
+        query_pending_actions(deps.as_ref(), None, None).unwrap(),
+        PendingActionsResponse {
+            delay,
+            actions: vec![
+                PendingAction {
+                    id: 0,
+                    action: action.clone(),
+                    eta,
+                },
+                PendingAction { id: 1, action, eta },
+            ],
+        }
+    );
+
+    // cannot execute before the eta
+    let info = mock_info("creator", &[]);
+    let msg = Execute::ExecuteScheduled { id: 0 };
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::TimelockNotReady { eta });
+
+    env.block.time = env.block.time.plus_seconds(delay);
+    let info = mock_info("creator", &[]);
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(BALANCE_CAP.load(deps.as_ref().storage).unwrap(), new_cap);
+
+    // cancelled actions are dropped
+    let info = mock_info("creator", &[]);
+    let msg = Execute::CancelScheduled { id: 1 };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = query_pending_actions(deps.as_ref(), None, None).unwrap();
+    assert!(res.actions.is_empty());
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::ExecuteScheduled { id: 1 };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+
+    // fee changes wait for the delay as well
+    let info = mock_info("creator", &[]);
+    let msg = Execute::SetTransferFee {
+        bps: 100,
+        collector: addr2.clone(),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::TimelockRequired {});
+    let msg = Execute::SetBurnRate { bps: 50 };
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::TimelockRequired {});
+
+    let actions = [
+        ScheduledAction::SetTransferFee {
+            bps: 100,
+            collector: addr2.clone(),
+        },
+        ScheduledAction::SetBurnRate { bps: 50 },
+    ];
+    for action in actions {
+        let msg = Execute::Schedule { action };
+        let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+    env.block.time = env.block.time.plus_seconds(delay);
+    for id in [2, 3] {
+        let msg = Execute::ExecuteScheduled { id };
+        let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+    assert_eq!(
+        TRANSFER_FEE.load(deps.as_ref().storage).unwrap(),
+        TransferFeeConfig {
+            bps: 100,
+            collector: Addr::unchecked(addr2),
+        }
+    );
+    assert_eq!(BURN_RATE.load(deps.as_ref().storage).unwrap(), 50);
+}
+
+#[test]
+fn test_risk_snapshot() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let frozen_amount = Uint128::from(500u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let bal_cap = Uint128::from(3000u128);
+    let limit = Uint128::from(1000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        frozen_amount,
+        bal_cap,
+    );
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::SetRateLimit { limit: Some(limit) };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::from(300u128),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("creator", &[]);
+    let msg = Execute::BlackList {
+        address: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let balance = amount1 - Uint128::from(300u128);
+    assert_eq!(
+        query_risk_snapshot(deps.as_ref(), mock_env(), addr1.clone()).unwrap(),
+        RiskSnapshotResponse {
+            balance,
+            frozen: frozen_amount,
+            cap_headroom: bal_cap - balance,
+            outbound_volume: Uint128::from(300u128),
+            rate_limit: Some(limit),
+            rate_limit_exempt: false,
+            account_frozen: false,
+            blacklisted: true,
+            burn_address: false,
+        }
+    );
+
+    // outbound volume resets with the window
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(RATE_LIMIT_WINDOW);
+    let res = query_risk_snapshot(deps.as_ref(), env, addr1).unwrap();
+    assert_eq!(res.outbound_volume, Uint128::zero());
+}
+
+#[test]
+fn test_self_lock() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let amount1 = Uint128::from(2000u128);
+    let addr1 = String::from("addr0001");
+    let amount2 = Uint128::from(2600u128);
+    let addr2 = String::from("addr0002");
+    let recovery = String::from("recovery");
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        amount1,
+        addr2.clone(),
+        amount2,
+        Uint128::zero(),
+        Uint128::from(3000u128),
+    );
+
+    let mut env = mock_env();
+    let until = env.block.time.seconds() + 1000;
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::SetRecoveryAddress {
+        address: Some(recovery.clone()),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::LockSelf { until };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        query_self_lock(deps.as_ref(), env.clone(), addr1.clone()).unwrap(),
+        SelfLockResponse {
+            locked_until: Some(until),
+            recovery_address: Some(recovery.clone()),
+        }
+    );
+
+    // outgoing transfers are rejected while locked
+    let transfer = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::from(1u128),
+    };
+    let info = mock_info(addr1.as_ref(), &[]);
+    let err = execute(deps.as_mut(), env.clone(), info, transfer.clone()).unwrap_err();
+    assert_eq!(err, ContractError::AccountLocked { until });
+
+    // the lock cannot be shortened nor the recovery address changed
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::LockSelf { until: until - 1 };
+    let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::CannotShortenLock {});
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::SetRecoveryAddress {
+        address: Some(addr2.clone()),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::AccountLocked { until });
+
+    // only the recovery address can unlock early
+    let msg = Execute::UnlockSelf {
+        owner: addr1.clone(),
+    };
+    let info = mock_info(addr1.as_ref(), &[]);
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info(recovery.as_ref(), &[]);
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let info = mock_info(addr1.as_ref(), &[]);
+    let _ = execute(deps.as_mut(), env.clone(), info, transfer.clone()).unwrap();
+
+    // without early unlock the lock simply expires
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::LockSelf { until };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    env.block.time = env.block.time.plus_seconds(1000);
+    let info = mock_info(addr1.as_ref(), &[]);
+    let _ = execute(deps.as_mut(), env.clone(), info, transfer).unwrap();
+    assert_eq!(
+        query_self_lock(deps.as_ref(), env, addr1)
+            .unwrap()
+            .locked_until,
+        None
+    );
+}
+
+#[test]
+fn test_sudo() {
+    let mut deps = mock_dependencies(&[Coin {
+        amount: Uint128::default(),
+        denom: String::default(),
+    }]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let new_cap = Uint128::from(5000u128);
+
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::from(2000u128),
+        addr2.clone(),
+        Uint128::from(2600u128),
+        Uint128::zero(),
+        Uint128::from(3000u128),
+    );
+
+    // governance is not bound by the timelock
+    let info = mock_info("creator", &[]);
+    let msg = Execute::SetTimelockDelay { delay: 3600 };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::UpdateBalanceCap { new_cap },
+    )
+    .unwrap();
+    assert!(res.attributes.iter().any(|attr| attr.value == "sudo"));
+    assert_eq!(BALANCE_CAP.load(deps.as_ref().storage).unwrap(), new_cap);
+
+    let _ = sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::FreezeAccount {
+            address: addr1.clone(),
+        },
+    )
+    .unwrap();
+    assert!(query_is_frozen(deps.as_ref(), addr1).unwrap().frozen);
+
+    let _ = sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::GrantRole {
+            address: addr2.clone(),
+            role: Role::Pauser,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        query_roles(deps.as_ref(), addr2).unwrap().roles,
+        vec![Role::Pauser]
+    );
+}
+
+// signs `payload` the way wallets do for permits: secp256k1 over sha256 of its JSON encoding
+fn sign_payload<T: serde::Serialize>(key: &SigningKey, payload: &T) -> Binary {
+    let signature: Signature = key.sign(&to_vec(payload).unwrap());
+    Binary::from(signature.as_ref())
+}
+
+#[test]
+fn test_permit_allowance() {
+    let mut deps = mock_dependencies(&[]);
+    let key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let pubkey = Binary::from(&key.verifying_key().to_bytes()[..]);
+    let owner = pubkey_to_address(&pubkey, "terra").unwrap();
+    let spender = String::from("addr0002");
+    let amount = Uint128::new(7000);
+    let expires = Expiration::AtHeight(123456);
+    do_instantiate(
+        deps.as_mut(),
+        owner.clone(),
+        Uint128::new(12340000),
+        String::from("addr0003"),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(100000000),
+    );
+
+    let env = mock_env();
+    let payload = PermitPayload {
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.to_string(),
+        owner: owner.clone(),
+        spender: spender.clone(),
+        amount,
+        nonce: 0,
+        expires,
+    };
+    let msg = Execute::PermitAllowance {
+        owner: owner.clone(),
+        spender: spender.clone(),
+        amount,
+        nonce: 0,
+        expires,
+        pubkey: pubkey.clone(),
+        signature: sign_payload(&key, &payload),
+    };
+
+    // anyone can submit the permit
+    let info = mock_info("relayer", &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.attributes[0].value, "permit_allowance");
+    let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+    assert_eq!(allowance.allowance, amount);
+    assert_eq!(allowance.expires, expires);
+    assert_eq!(
+        query_permit_nonce(deps.as_ref(), owner.clone())
+            .unwrap()
+            .nonce,
+        1
+    );
+
+    // replaying the same permit is rejected
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidNonce { expected: 1 });
+
+    // a permit signed by someone else is rejected
+    let other = SigningKey::from_bytes(&[2u8; 32]).unwrap();
+    let payload = PermitPayload {
+        nonce: 1,
+        amount: Uint128::new(1),
+        ..payload
+    };
+    let msg = Execute::PermitAllowance {
+        owner: owner.clone(),
+        spender: spender.clone(),
+        amount: payload.amount,
+        nonce: 1,
+        expires,
+        pubkey: pubkey.clone(),
+        signature: sign_payload(&other, &payload),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidSignature {});
+
+    // so is a pubkey that does not belong to the owner
+    let msg = Execute::PermitAllowance {
+        owner: owner.clone(),
+        spender: spender.clone(),
+        amount: payload.amount,
+        nonce: 1,
+        expires,
+        pubkey: Binary::from(&other.verifying_key().to_bytes()[..]),
+        signature: sign_payload(&other, &payload),
+    };
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidPubkey {});
+
+    // and an expired permit
+    let expired = Expiration::AtHeight(env.block.height);
+    let payload = PermitPayload {
+        expires: expired,
+        ..payload
+    };
+    let msg = Execute::PermitAllowance {
+        owner: owner.clone(),
+        spender,
+        amount: payload.amount,
+        nonce: 1,
+        expires: expired,
+        pubkey,
+        signature: sign_payload(&key, &payload),
+    };
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Expired {});
+    assert_eq!(query_permit_nonce(deps.as_ref(), owner).unwrap().nonce, 1);
+}
+
+#[test]
+fn test_permit_and_call() {
+    let mut deps = mock_dependencies(&[]);
+    let key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let pubkey = Binary::from(&key.verifying_key().to_bytes()[..]);
+    let owner = pubkey_to_address(&pubkey, "terra").unwrap();
+    let spender = String::from("addr0002");
+    let contract = String::from("vault0000");
+    let amount = Uint128::new(7000);
+    let call = Binary::from(r#"{"deposit":{}}"#.as_bytes());
+    do_instantiate(
+        deps.as_mut(),
+        owner.clone(),
+        Uint128::new(12340000),
+        String::from("addr0003"),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(100000000),
+    );
+
+    let env = mock_env();
+    let payload = PermitPayload {
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.to_string(),
+        owner: owner.clone(),
+        spender: spender.clone(),
+        amount,
+        nonce: 0,
+        expires: Expiration::Never {},
+    };
+    let msg = Execute::PermitAndCall {
+        permit: Permit {
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount,
+            nonce: 0,
+            expires: Expiration::Never {},
+            pubkey,
+            signature: sign_payload(&key, &payload),
+        },
+        call: call.clone(),
+        contract: contract.clone(),
+    };
+
+    // only the spender the owner signed for can use the permit
+    let info = mock_info("relayer", &[]);
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let info = mock_info(spender.as_ref(), &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    let binary_msg = Cw20ReceiveMsg {
+        sender: spender.clone(),
+        amount,
+        msg: call,
+    }
+    .into_binary()
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_always(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract.clone(),
+                msg: binary_msg,
+                funds: vec![],
+            }),
+            0
+        )]
+    );
+    assert_eq!(get_balance(deps.as_ref(), &owner), Uint128::new(12333000));
+    assert_eq!(get_balance(deps.as_ref(), &contract), amount);
+    // the whole permitted amount went out, nothing is left to spend
+    let allowance = query_allowance(deps.as_ref(), owner.clone(), spender).unwrap();
+    assert_eq!(allowance.allowance, Uint128::zero());
+    assert_eq!(query_permit_nonce(deps.as_ref(), owner).unwrap().nonce, 1);
+
+    // and the permit cannot be used twice
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidNonce { expected: 1 });
+}
+
+#[test]
+fn test_transfer_with_authorization() {
+    let mut deps = mock_dependencies(&[]);
+    let key = SigningKey::from_bytes(&[1u8; 32]).unwrap();
+    let pubkey = Binary::from(&key.verifying_key().to_bytes()[..]);
+    let from = pubkey_to_address(&pubkey, "terra").unwrap();
+    let to = String::from("addr0002");
+    let amount = Uint128::new(7000);
+    do_instantiate(
+        deps.as_mut(),
+        from.clone(),
+        Uint128::new(12340000),
+        to.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(100000000),
+    );
+
+    let env = mock_env();
+    let now = env.block.time.seconds();
+    let authorization = TransferAuthorization {
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.to_string(),
+        from: from.clone(),
+        to: to.clone(),
+        amount,
+        valid_after: now - 10,
+        valid_before: now + 10,
+        nonce: Binary::from(b"nonce-1"),
+    };
+    let authorize = |authorization: &TransferAuthorization| Execute::TransferWithAuthorization {
+        from: authorization.from.clone(),
+        to: authorization.to.clone(),
+        amount: authorization.amount,
+        valid_after: authorization.valid_after,
+        valid_before: authorization.valid_before,
+        nonce: authorization.nonce.clone(),
+        pubkey: pubkey.clone(),
+        signature: sign_payload(&key, authorization),
+    };
+
+    // a relayer submits the transfer on behalf of the signer
+    let info = mock_info("relayer", &[]);
+    let msg = authorize(&authorization);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.attributes[0].value, "transfer");
+    assert_eq!(get_balance(deps.as_ref(), &from), Uint128::new(12333000));
+    assert_eq!(get_balance(deps.as_ref(), &to), amount);
+    assert!(
+        query_authorization_state(deps.as_ref(), from.clone(), authorization.nonce.clone())
+            .unwrap()
+            .used
+    );
+
+    // every nonce works once
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::AuthorizationUsed {});
+
+    // tampering with the signed payload is detected
+    let mut msg = authorize(&TransferAuthorization {
+        nonce: Binary::from(b"nonce-2"),
+        ..authorization.clone()
+    });
+    if let Execute::TransferWithAuthorization { amount, .. } = &mut msg {
+        *amount = Uint128::new(8000);
+    }
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidSignature {});
+
+    // the validity window is enforced
+    let msg = authorize(&TransferAuthorization {
+        nonce: Binary::from(b"nonce-2"),
+        valid_after: now + 5,
+        ..authorization.clone()
+    });
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::AuthorizationNotYetValid {
+            valid_after: now + 5
+        }
+    );
+    let msg = authorize(&TransferAuthorization {
+        nonce: Binary::from(b"nonce-2"),
+        valid_before: now,
+        ..authorization
+    });
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Expired {});
+    assert!(
+        !query_authorization_state(deps.as_ref(), from, Binary::from(b"nonce-2"))
+            .unwrap()
+            .used
+    );
+}
+
+#[test]
+fn test_snapshots() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(2000),
+        addr2.clone(),
+        Uint128::new(1000),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let start = mock_env().block.height;
+    let balance_at = |deps: Deps, address: &String, height: u64| {
+        query_balance_at(deps, address.clone(), height)
+            .unwrap()
+            .balance
+    };
+
+    let mut env = mock_env();
+    env.block.height = start + 1;
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(500),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    env.block.height = start + 3;
+    let msg = Execute::Burn {
+        amount: Uint128::new(300),
+    };
+    let _ = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // balances are reported as of the beginning of the block
+    assert_eq!(balance_at(deps.as_ref(), &addr1, start), Uint128::zero());
+    assert_eq!(
+        balance_at(deps.as_ref(), &addr1, start + 1),
+        Uint128::new(2000)
+    );
+    assert_eq!(
+        balance_at(deps.as_ref(), &addr2, start + 1),
+        Uint128::new(1000)
+    );
+    assert_eq!(
+        balance_at(deps.as_ref(), &addr1, start + 2),
+        Uint128::new(1500)
+    );
+    assert_eq!(
+        balance_at(deps.as_ref(), &addr2, start + 2),
+        Uint128::new(1500)
+    );
+    assert_eq!(
+        balance_at(deps.as_ref(), &addr1, start + 4),
+        Uint128::new(1200)
+    );
+    assert_eq!(
+        balance_at(deps.as_ref(), &String::from("addr0003"), start + 4),
+        Uint128::zero()
+    );
+
+    let total_supply_at = |height| {
+        query_total_supply_at(deps.as_ref(), height)
+            .unwrap()
+            .total_supply
+    };
+    assert_eq!(total_supply_at(start + 3), Uint128::new(3000));
+    assert_eq!(total_supply_at(start + 4), Uint128::new(2700));
+}
+
+#[test]
+fn test_delegation() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(2000),
+        addr2.clone(),
+        Uint128::new(1000),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let start = mock_env().block.height;
+    let power =
+        |deps: Deps, address: &String| query_voting_power(deps, address.clone()).unwrap().power;
+    let power_at = |deps: Deps, address: &String, height: u64| {
+        query_voting_power_at(deps, address.clone(), height)
+            .unwrap()
+            .power
+    };
+
+    // holders vote for themselves by default
+    assert_eq!(power(deps.as_ref(), &addr1), Uint128::new(2000));
+    assert_eq!(
+        query_delegation(deps.as_ref(), addr1.clone())
+            .unwrap()
+            .delegate,
+        addr1
+    );
+
+    let mut env = mock_env();
+    env.block.height = start + 1;
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Delegate {
+        delegatee: addr2.clone(),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(power(deps.as_ref(), &addr1), Uint128::zero());
+    assert_eq!(power(deps.as_ref(), &addr2), Uint128::new(3000));
+
+    // the delegated power follows later balance changes
+    env.block.height = start + 2;
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: Uint128::new(500),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(power(deps.as_ref(), &addr2), Uint128::new(2500));
+    assert_eq!(power(deps.as_ref(), &addr3), Uint128::new(500));
+
+    // delegating to oneself takes the votes back
+    env.block.height = start + 3;
+    let msg = Execute::Delegate {
+        delegatee: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(power(deps.as_ref(), &addr1), Uint128::new(1500));
+    assert_eq!(power(deps.as_ref(), &addr2), Uint128::new(1000));
+
+    assert_eq!(
+        power_at(deps.as_ref(), &addr2, start + 1),
+        Uint128::new(1000)
+    );
+    assert_eq!(
+        power_at(deps.as_ref(), &addr2, start + 2),
+        Uint128::new(3000)
+    );
+    assert_eq!(
+        power_at(deps.as_ref(), &addr2, start + 3),
+        Uint128::new(2500)
+    );
+    assert_eq!(power_at(deps.as_ref(), &addr1, start + 3), Uint128::zero());
+    assert_eq!(
+        power_at(deps.as_ref(), &addr1, start + 4),
+        Uint128::new(1500)
+    );
+}
+
+#[test]
+fn test_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(2000),
+        addr2.clone(),
+        Uint128::new(1000),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let pending =
+        |deps: Deps, address: &String| query_pending_rewards(deps, address.clone()).unwrap().amount;
+    let distribute = |amount: u128| Execute::DistributeRewards {
+        amount: Uint128::new(amount),
+    };
+
+    // nothing can be distributed before the denom is set
+    let info = mock_info("distributor", &coins(300, "uusd"));
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), distribute(300)).unwrap_err();
+    assert_eq!(err, ContractError::RewardDenomNotSet {});
+
+    let msg = Execute::SetRewardDenom {
+        denom: String::from("uusd"),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr1.as_ref(), &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let creator = mock_info("creator", &[]);
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg.clone()).unwrap();
+
+    // the sent funds must match the distributed amount
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), distribute(200)).unwrap_err();
+    assert_eq!(err, ContractError::RewardFundsMismatch {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("distributor", &coins(300, "uluna")),
+        distribute(300),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::EmptyBalance {
+            denom: String::from("uusd")
+        }
+    );
+
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), distribute(300)).unwrap();
+    assert_eq!(pending(deps.as_ref(), &addr1), Uint128::new(200));
+    assert_eq!(pending(deps.as_ref(), &addr2), Uint128::new(100));
+    let err = execute(deps.as_mut(), mock_env(), creator, msg).unwrap_err();
+    assert_eq!(err, ContractError::RewardsAlreadyDistributed {});
+
+    // later distributions follow the new balances, earned rewards stay
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(1000),
+    };
+    let owner = mock_info(addr1.as_ref(), &[]);
+    let _ = execute(deps.as_mut(), mock_env(), owner.clone(), msg).unwrap();
+    let _ = execute(deps.as_mut(), mock_env(), info, distribute(300)).unwrap();
+    assert_eq!(pending(deps.as_ref(), &addr1), Uint128::new(300));
+    assert_eq!(pending(deps.as_ref(), &addr2), Uint128::new(300));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        owner.clone(),
+        Execute::ClaimRewards {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: addr1.clone(),
+            amount: coins(300, "uusd"),
+        })]
+    );
+    assert_eq!(pending(deps.as_ref(), &addr1), Uint128::zero());
+    let err = execute(deps.as_mut(), mock_env(), owner, Execute::ClaimRewards {}).unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+}
+
+#[test]
+fn test_staking() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(2000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let env = mock_env();
+    let contract = env.contract.address.to_string();
+
+    let creator = mock_info("creator", &[]);
+    let msg = Execute::SetRateLimit {
+        limit: Some(Uint128::new(100)),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), creator.clone(), msg).unwrap();
+    let msg = Execute::UpdateStakingConfig {
+        unbonding_period: 100,
+        rate_limit_boost: Decimal::percent(50),
+    };
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(addr1.as_ref(), &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let _ = execute(deps.as_mut(), env.clone(), creator, msg).unwrap();
+
+    // staked tokens are held by the contract and raise the rate limit
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Stake {
+        amount: Uint128::new(1000),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(1000));
+    assert_eq!(get_balance(deps.as_ref(), &contract), Uint128::new(1000));
+    assert_eq!(
+        query_staked(deps.as_ref(), addr1.clone()).unwrap(),
+        StakedResponse {
+            stake: Uint128::new(1000),
+            rate_limit: Some(Uint128::new(600)),
+        }
+    );
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(600),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // cannot unstake more than staked
+    let msg = Execute::Unstake {
+        amount: Uint128::new(1001),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+
+    // unstaked tokens come back after the unbonding period
+    let msg = Execute::Unstake {
+        amount: Uint128::new(400),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    assert_eq!(
+        query_staked(deps.as_ref(), addr1.clone()).unwrap().stake,
+        Uint128::new(600)
+    );
+    let claims = query_claims(deps.as_ref(), addr1.clone()).unwrap().claims;
+    assert_eq!(claims.len(), 1);
+    assert_eq!(claims[0].amount, Uint128::new(400));
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        Execute::ClaimUnstaked {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+
+    let mut env = env;
+    env.block.time = env.block.time.plus_seconds(100);
+    let _ = execute(deps.as_mut(), env, info, Execute::ClaimUnstaked {}).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(800));
+    assert_eq!(get_balance(deps.as_ref(), &contract), Uint128::new(600));
+    assert!(query_claims(deps.as_ref(), addr1)
+        .unwrap()
+        .claims
+        .is_empty());
+}
+
+#[test]
+fn test_staking_escrow() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(2000),
+        addr2.clone(),
+        Uint128::new(1000),
+        Uint128::zero(),
+        Uint128::new(2000),
+    );
+    let mut env = mock_env();
+    let contract = env.contract.address.to_string();
+    let pending =
+        |deps: Deps, address: &String| query_pending_rewards(deps, address.clone()).unwrap().amount;
+
+    let creator = mock_info("creator", &[]);
+    let msg = Execute::SetRewardDenom {
+        denom: String::from("uusd"),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), creator, msg).unwrap();
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Stake {
+        amount: Uint128::new(1000),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // staked tokens keep earning for their owner, the escrow earns nothing
+    let distribute = Execute::DistributeRewards {
+        amount: Uint128::new(300),
+    };
+    let distributor = mock_info("distributor", &coins(300, "uusd"));
+    let _ = execute(
+        deps.as_mut(),
+        env.clone(),
+        distributor.clone(),
+        distribute.clone(),
+    )
+    .unwrap();
+    assert_eq!(pending(deps.as_ref(), &addr1), Uint128::new(200));
+    assert_eq!(pending(deps.as_ref(), &addr2), Uint128::new(100));
+    let holders = query_top_holders(deps.as_ref(), None, None)
+        .unwrap()
+        .holders;
+    assert!(holders.iter().all(|holder| holder.address != contract));
+
+    // the claimant fills up to the cap while the stake unbonds
+    let msg = Execute::Transfer {
+        recipient: addr1.clone(),
+        amount: Uint128::new(1000),
+    };
+    let _ = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(addr2.as_ref(), &[]),
+        msg,
+    )
+    .unwrap();
+    let msg = Execute::Unstake {
+        amount: Uint128::new(1000),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // unbonding tokens no longer earn, what the stake earned stays. addr1 holds the
+    // whole earning supply, so it gets the whole distribution.
+    let _ = execute(deps.as_mut(), env.clone(), distributor, distribute).unwrap();
+    assert_eq!(pending(deps.as_ref(), &addr1), Uint128::new(500));
+    assert_eq!(pending(deps.as_ref(), &addr2), Uint128::new(100));
+
+    // claiming back the stake cannot exceed the balance cap
+    env.block.time = env.block.time.plus_seconds(1);
+    let err = execute(deps.as_mut(), env, info, Execute::ClaimUnstaked {}).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+}
+
+#[test]
+fn test_rewards_fully_claimable() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let sink = String::from("sink");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(2000),
+        addr2.clone(),
+        Uint128::new(1000),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let creator = mock_info("creator", &[]);
+    let msg = Execute::SetRewardDenom {
+        denom: String::from("uusd"),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+
+    // part of the supply sits at a burn address and part unbonds in the escrow
+    let msg = Execute::SetBurnAddress {
+        address: sink.clone(),
+        burn: true,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator, msg).unwrap();
+    let msg = Execute::Transfer {
+        recipient: sink.clone(),
+        amount: Uint128::new(500),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(&addr1, &[]), msg).unwrap();
+    for msg in [
+        Execute::Stake {
+            amount: Uint128::new(600),
+        },
+        Execute::Unstake {
+            amount: Uint128::new(500),
+        },
+    ] {
+        let _ = execute(deps.as_mut(), mock_env(), mock_info(&addr2, &[]), msg).unwrap();
+    }
+
+    let msg = Execute::DistributeRewards {
+        amount: Uint128::new(1000),
+    };
+    let info = mock_info("distributor", &coins(1000, "uusd"));
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // every distributed reward goes to someone who can claim it
+    let mut claimed = Uint128::zero();
+    for address in [&addr1, &addr2] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(address, &[]),
+            Execute::ClaimRewards {},
+        )
+        .unwrap();
+        claimed += Uint128::new(res.attributes[2].value.parse().unwrap());
+    }
+    assert_eq!(claimed, Uint128::new(1000));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&sink, &[]),
+        Execute::ClaimRewards {},
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NothingToClaim {});
+}
+
+fn airdrop_leaf(address: &str, amount: u128) -> [u8; 32] {
+    Sha256::digest(format!("{}{}", address, amount).as_bytes()).into()
+}
+
+#[test]
+fn test_airdrop() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(2000),
+        String::from("addr0003"),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+
+    // two leaves, each being the proof of the other
+    let leaf1 = airdrop_leaf(&addr1, 1000);
+    let leaf2 = airdrop_leaf(&addr2, 500);
+    let (first, second) = if leaf1 < leaf2 {
+        (leaf1, leaf2)
+    } else {
+        (leaf2, leaf1)
+    };
+    let root = hex::encode(Sha256::new().chain(first).chain(second).finalize());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("creator", &[]),
+        Execute::RegisterMerkleRoot {
+            merkle_root: String::from("not hex"),
+            total_amount: Uint128::new(1500),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidMerkleRoot {});
+    let msg = Execute::RegisterMerkleRoot {
+        merkle_root: root.clone(),
+        total_amount: Uint128::new(1500),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr1.as_ref(), &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    assert_eq!(res.attributes[1].value, "1");
+
+    // a wrong amount does not match the leaf
+    let info = mock_info(addr1.as_ref(), &[]);
+    let claim = |amount: u128, proof: [u8; 32]| Execute::ClaimAirdrop {
+        stage: 1,
+        amount: Uint128::new(amount),
+        proof: vec![hex::encode(proof)],
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), claim(1500, leaf2)).unwrap_err();
+    assert_eq!(err, ContractError::InvalidProof {});
+
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), claim(1000, leaf2)).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(3000));
+    assert_eq!(
+        query_token_info(deps.as_ref()).unwrap().total_supply,
+        Uint128::new(3000)
+    );
+    assert!(
+        query_is_claimed(deps.as_ref(), 1, addr1.clone())
+            .unwrap()
+            .claimed
+    );
+    let err = execute(deps.as_mut(), mock_env(), info, claim(1000, leaf2)).unwrap_err();
+    assert_eq!(err, ContractError::AirdropClaimed {});
+
+    let info = mock_info(addr2.as_ref(), &[]);
+    let _ = execute(deps.as_mut(), mock_env(), info, claim(500, leaf1)).unwrap();
+    assert_eq!(
+        query_airdrop_stage(deps.as_ref(), 1).unwrap(),
+        AirdropStageResponse {
+            stage: 1,
+            merkle_root: root,
+            total_amount: Uint128::new(1500),
+            claimed_amount: Uint128::new(1500),
+        }
+    );
+    assert!(
+        !query_is_claimed(deps.as_ref(), 1, String::from("addr0003"))
+            .unwrap()
+            .claimed
+    );
+}
+
+#[test]
+fn test_transfer_fee() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let collector = String::from("collector");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(10000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(100000),
+    );
+
+    let msg = Execute::SetTransferFee {
+        bps: 100,
+        collector: collector.clone(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr1.as_ref(), &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let creator = mock_info("creator", &[]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        creator.clone(),
+        Execute::SetTransferFee {
+            bps: 10001,
+            collector: collector.clone(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidTransferFee {});
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+
+    // the recipient gets the amount minus the fee
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(1000),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "fee" && attr.value == "10"));
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(9000));
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(990));
+    assert_eq!(get_balance(deps.as_ref(), &collector), Uint128::new(10));
+
+    // spenders pay the fee out of their allowance
+    let msg = Execute::IncreaseAllowance {
+        spender: addr2.clone(),
+        amount: Uint128::new(500),
+        expires: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let msg = Execute::TransferFrom {
+        owner: addr1.clone(),
+        recipient: addr2.clone(),
+        amount: Uint128::new(500),
+    };
+    let _ = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr2.as_ref(), &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(8500));
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(1485));
+    assert_eq!(get_balance(deps.as_ref(), &collector), Uint128::new(15));
+    assert_eq!(
+        query_allowance(deps.as_ref(), addr1.clone(), addr2.clone())
+            .unwrap()
+            .allowance,
+        Uint128::zero()
+    );
+
+    // receiving contracts are told the amount they actually got
+    let contract = String::from("contract0000");
+    let msg = Execute::Send {
+        contract: contract.clone(),
+        amount: Uint128::new(2000),
+        msg: Binary::default(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let expected = Cw20ReceiveMsg {
+        sender: addr1.clone(),
+        amount: Uint128::new(1980),
+        msg: Binary::default(),
+    }
+    .into_cosmos_msg(contract.clone())
+    .unwrap();
+    assert_eq!(res.messages[0].msg, expected);
+    assert_eq!(get_balance(deps.as_ref(), &contract), Uint128::new(1980));
+
+    // the fee collector is held to the transfer checks too, and nothing is charged before
+    // every check passed
+    let msg = Execute::BlackList {
+        address: collector.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(1000),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::Blacklisted {});
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(6500));
+    assert_eq!(get_balance(deps.as_ref(), &collector), Uint128::new(35));
+
+    // exempt senders and recipients pay nothing
+    assert_eq!(
+        query_transfer_fee(
+            deps.as_ref(),
+            addr1.clone(),
+            addr2.clone(),
+            Uint128::new(300)
+        )
+        .unwrap(),
+        TransferFeeResponse {
+            fee: Uint128::new(3),
+            burned: Uint128::zero(),
+            amount_received: Uint128::new(297),
+        }
+    );
+    let msg = Execute::SetFeeExemption {
+        address: addr2.clone(),
+        exempt: true,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator, msg).unwrap();
+    assert_eq!(
+        query_transfer_fee(deps.as_ref(), addr1, addr2, Uint128::new(300))
+            .unwrap()
+            .fee,
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn test_burn_tax() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(10000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(100000),
+    );
+
+    let msg = Execute::SetBurnRate { bps: 10 };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr1.as_ref(), &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let creator = mock_info("creator", &[]);
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+
+    // the fee and the burn rate together cannot take more than the whole transfer
+    let msg = Execute::SetTransferFee {
+        bps: 9995,
+        collector: String::from("collector"),
+    };
+    let err = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidTransferFee {});
+
+    // the tax is burned out of the sender's balance and the total supply
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(5000),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "burned" && attr.value == "5"));
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(5000));
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(4995));
+    assert_eq!(
+        query_token_info(deps.as_ref()).unwrap().total_supply,
+        Uint128::new(9995)
+    );
+    assert_eq!(
+        query_burn_stats(deps.as_ref()).unwrap(),
+        BurnStatsResponse {
+            burn_rate_bps: 10,
+            total_burned: Uint128::new(5),
+        }
+    );
+
+    // exempt addresses are not taxed
+    let msg = Execute::SetFeeExemption {
+        address: addr2.clone(),
+        exempt: true,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator, msg).unwrap();
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(1000),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(5995));
+    assert_eq!(
+        query_burn_stats(deps.as_ref()).unwrap().total_burned,
+        Uint128::new(5)
+    );
+}
+
+#[test]
+fn test_transfer_policy() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let creator = mock_info("creator", &[]);
+    assert_eq!(
+        query_transfer_policy(deps.as_ref()).unwrap().policy,
+        TransferPolicy::Open
+    );
+
+    let msg = Execute::SetTransferPolicy {
+        policy: TransferPolicy::WhitelistedOnly,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr1.as_ref(), &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+
+    // both parties have to be whitelisted
+    let info = mock_info(addr1.as_ref(), &[]);
+    let transfer = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(100),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotWhitelisted {
+            address: addr1.clone()
+        }
+    );
+    let msg = Execute::SetWhitelisted {
+        address: addr2.clone(),
+        whitelisted: true,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    assert!(
+        query_is_whitelisted(deps.as_ref(), addr2.clone())
+            .unwrap()
+            .whitelisted
+    );
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::NotWhitelisted {
+            address: addr1.clone()
+        }
+    );
+
+    // only recipients are checked once the policy is relaxed, mints included
+    let msg = Execute::SetTransferPolicy {
+        policy: TransferPolicy::WhitelistedRecipients,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let _ = execute(deps.as_mut(), mock_env(), info, transfer).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(100));
+    let msg = Execute::Mint {
+        recipient: addr1.clone(),
+        amount: Uint128::new(100),
+    };
+    let err = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::NotWhitelisted { address: addr1 });
+
+    // switching the policy has to be scheduled while a timelock delay is set
+    let msg = Execute::SetTimelockDelay { delay: 3600 };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let msg = Execute::SetTransferPolicy {
+        policy: TransferPolicy::Open,
+    };
+    let err = execute(deps.as_mut(), mock_env(), creator, msg).unwrap_err();
+    assert_eq!(err, ContractError::TimelockRequired {});
+}
+
+#[test]
+fn test_kyc_attestations() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let provider = String::from("provider");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let creator = mock_info("creator", &[]);
+    let msg = Execute::GrantRole {
+        address: provider.clone(),
+        role: Role::KycProvider,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let msg = Execute::SetKycThreshold {
+        threshold: Some(Uint128::new(100)),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+
+    // transfers up to the threshold need no attestation
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(100),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // only KYC providers can attest
+    let expires = mock_env().block.time.seconds() + 3600;
+    let msg = Execute::AttestBatch {
+        attestations: vec![
+            AttestationEntry {
+                address: addr1.clone(),
+                level: 2,
+                expires,
+            },
+            AttestationEntry {
+                address: addr2.clone(),
+                level: 1,
+                expires: mock_env().block.time.seconds(),
+            },
+        ],
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(&provider, &[]), msg).unwrap();
+    assert_eq!(
+        query_attestation(deps.as_ref(), mock_env(), addr1.clone()).unwrap(),
+        AttestationResponse {
+            attestation: Some(Attestation { level: 2, expires }),
+            valid: true,
+            kyc_threshold: Some(Uint128::new(100)),
+        }
+    );
+
+    // both parties need an unexpired attestation above the threshold
+    let transfer = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(101),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::AttestationRequired {
+            address: addr2.clone()
+        }
+    );
+    let msg = Execute::Attest {
+        address: addr2.clone(),
+        level: 1,
+        expires,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(&provider, &[]), msg).unwrap();
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(201));
+
+    let msg = Execute::RevokeAttestation {
+        address: addr1.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info(&provider, &[]), msg).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info, transfer).unwrap_err();
+    assert_eq!(err, ContractError::AttestationRequired { address: addr1 });
+}
+
+#[test]
+fn test_travel_rule() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let msg = Execute::SetTravelRuleThreshold {
+        threshold: Some(Uint128::new(500)),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+    // plain transfers above the threshold are rejected
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(501),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MemoRequired {
+            threshold: Uint128::new(500)
+        }
+    );
+
+    let msg = Execute::TransferWithMemo {
+        recipient: addr2.clone(),
+        amount: Uint128::new(501),
+        memo_hash: String::from("not hex"),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidMemoHash {});
+
+    // the memo hash is recorded along with the transfer
+    let memo_hash = hex::encode(Sha256::digest(b"travel rule payload"));
+    let msg = Execute::TransferWithMemo {
+        recipient: addr2.clone(),
+        amount: Uint128::new(501),
+        memo_hash: memo_hash.clone(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "memo_hash" && attr.value == memo_hash));
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(501));
+    assert_eq!(
+        query_transfer_memos(deps.as_ref(), None, None).unwrap(),
+        TransferMemosResponse {
+            threshold: Some(Uint128::new(500)),
+            memos: vec![TransferMemo {
+                id: 0,
+                from: Addr::unchecked(addr1),
+                to: Addr::unchecked(addr2),
+                amount: Uint128::new(501),
+                memo_hash,
+                height: mock_env().block.height,
+            }],
+        }
+    );
+}
+
+#[test]
+fn test_account_stats() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+
+    let mut env = mock_env();
+    env.block.height += 1;
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(300),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = Execute::Burn {
+        amount: Uint128::new(100),
+    };
+    let _ = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // the initial balance counts as received
+    assert_eq!(
+        query_account_stats(deps.as_ref(), addr1).unwrap(),
+        AccountStatsResponse {
+            tx_count: 3,
+            total_sent: Uint128::new(400),
+            total_received: Uint128::new(1000),
+            last_activity_height: env.block.height,
+        }
+    );
+    assert_eq!(
+        query_account_stats(deps.as_ref(), addr2).unwrap(),
+        AccountStatsResponse {
+            tx_count: 1,
+            total_sent: Uint128::zero(),
+            total_received: Uint128::new(300),
+            last_activity_height: env.block.height,
+        }
+    );
+}
+
+#[test]
+fn test_supply_breakdown() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        addr2.clone(),
+        Uint128::new(500),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+
+    let creator = mock_info("creator", &[]);
+    let msg = Execute::UpdateFrozenList(UpdateType::Add(Cw20Coin {
+        address: addr2,
+        amount: Uint128::new(200),
+    }));
+    let _ = execute(deps.as_mut(), mock_env(), creator, msg).unwrap();
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Burn {
+        amount: Uint128::new(50),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let msg = Execute::Stake {
+        amount: Uint128::new(300),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    assert_eq!(
+        query_supply_breakdown(deps.as_ref(), mock_env()).unwrap(),
+        SupplyBreakdownResponse {
+            total_supply: Uint128::new(1450),
+            circulating_supply: Uint128::new(950),
+            escrowed: Uint128::new(300),
+            frozen: Uint128::new(200),
+            total_minted: Uint128::new(1500),
+            total_burned: Uint128::new(50),
+        }
+    );
+}
+
+#[test]
+fn test_token_hooks() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let hook = String::from("accounting");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+
+    // only admins register hooks, each at most once
+    let msg = Execute::AddTokenHook {
+        address: hook.clone(),
+    };
+    let info = mock_info(addr1.as_ref(), &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let creator = mock_info("creator", &[]);
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg.clone()).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Hook(HookError::HookAlreadyRegistered {})
+    );
+    assert_eq!(
+        query_token_hooks(deps.as_ref()).unwrap().hooks,
+        vec![hook.clone()]
+    );
+
+    // hooks are told about transfers and burns
+    let msg = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(300),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let notification = TokenHookMsg::Transfer {
+        sender: addr1.clone(),
+        recipient: addr2.clone(),
+        amount: Uint128::new(300),
+    }
+    .into_cosmos_msg(hook.clone())
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_error(notification, u64::MAX)]
+    );
+    let msg = Execute::Burn {
+        amount: Uint128::new(100),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let notification = TokenHookMsg::Burn {
+        owner: addr1.clone(),
+        amount: Uint128::new(100),
+    }
+    .into_cosmos_msg(hook.clone())
+    .unwrap();
+    assert_eq!(res.messages[0].msg, notification);
+
+    // a failing hook does not undo the transfer
+    let failed = Reply {
+        id: u64::MAX,
+        result: ContractResult::Err(String::from("boom")),
+    };
+    let res = reply(deps.as_mut(), mock_env(), failed).unwrap();
+    assert_eq!(res.events[0].ty, "token_hook_failed");
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(600));
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(300));
+
+    // sends are only reported once the receiving contract accepted them
+    let msg = Execute::Send {
+        contract: String::from("contract0000"),
+        amount: Uint128::new(100),
+        msg: Binary::default(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    let ok = Reply {
+        id: 0,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), ok).unwrap();
+    let notification = TokenHookMsg::Transfer {
+        sender: addr1.clone(),
+        recipient: String::from("contract0000"),
+        amount: Uint128::new(100),
+    }
+    .into_cosmos_msg(hook.clone())
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_error(notification, u64::MAX)]
+    );
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let failed = Reply {
+        id: 1,
+        result: ContractResult::Err(String::from("boom")),
+    };
+    let res = reply(deps.as_mut(), mock_env(), failed).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(get_balance(deps.as_ref(), &addr1), Uint128::new(500));
+
+    // every mint is reported, batch mints and airdrop claims included
+    let mint_notification = |recipient: &String, amount: u128| {
+        let msg = TokenHookMsg::Mint {
+            recipient: recipient.clone(),
+            amount: Uint128::new(amount),
+        }
+        .into_cosmos_msg(hook.clone())
+        .unwrap();
+        SubMsg::reply_on_error(msg, u64::MAX)
+    };
+    let msg = Execute::AddMinter {
+        address: String::from("minter"),
+        cap: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let msg = Execute::MintBatch {
+        recipients: vec![
+            Cw20Coin {
+                address: addr1.clone(),
+                amount: Uint128::new(10),
+            },
+            Cw20Coin {
+                address: addr2.clone(),
+                amount: Uint128::new(20),
+            },
+        ],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![mint_notification(&addr1, 10), mint_notification(&addr2, 20)]
+    );
+
+    // a single leaf tree is its own root
+    let msg = Execute::RegisterMerkleRoot {
+        merkle_root: hex::encode(airdrop_leaf(&addr2, 50)),
+        total_amount: Uint128::new(50),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let msg = Execute::ClaimAirdrop {
+        stage: 1,
+        amount: Uint128::new(50),
+        proof: vec![],
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(addr2.as_ref(), &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![mint_notification(&addr2, 50)]);
+
+    let msg = Execute::RemoveTokenHook { address: hook };
+    let _ = execute(deps.as_mut(), mock_env(), creator, msg).unwrap();
+    assert!(query_token_hooks(deps.as_ref()).unwrap().hooks.is_empty());
+}
+
+/// Answers every smart query with `response`, or fails like a missing contract without one
+struct PolicyQuerier(Option<PolicyResponse>);
+
+impl Querier for PolicyQuerier {
+    fn raw_query(&self, _request: &[u8]) -> QuerierResult {
+        match &self.0 {
+            Some(response) => SystemResult::Ok(ContractResult::Ok(to_binary(response).unwrap())),
+            None => SystemResult::Err(SystemError::NoSuchContract {
+                addr: String::from("policy"),
+            }),
+        }
+    }
+}
+
+#[test]
+fn test_policy_contract() {
+    let mut deps = OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: PolicyQuerier(None),
+    };
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(1000),
+        addr2.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let creator = mock_info("creator", &[]);
+    let msg = Execute::SetPolicyContract {
+        address: Some(String::from("policy")),
+        fail_open: false,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    assert_eq!(
+        query_policy_contract(deps.as_ref()).unwrap(),
+        PolicyContractResponse {
+            address: Some(String::from("policy")),
+            fail_open: false,
+        }
+    );
+
+    // an unreachable policy blocks transfers unless configured to fail open
+    let info = mock_info(addr1.as_ref(), &[]);
+    let transfer = Execute::Transfer {
+        recipient: addr2.clone(),
+        amount: Uint128::new(100),
+    };
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap_err();
+    assert_eq!(err, ContractError::PolicyUnreachable {});
+    let msg = Execute::SetPolicyContract {
+        address: Some(String::from("policy")),
+        fail_open: true,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap();
+
+    // denials carry the policy's reason
+    deps.querier = PolicyQuerier(Some(PolicyResponse {
+        allowed: false,
+        reason: Some(String::from("sanctioned")),
+    }));
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), transfer.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PolicyDenied {
+            reason: String::from("sanctioned")
+        }
+    );
+
+    // airdrop claims mint, so the policy can deny them as well
+    let msg = Execute::RegisterMerkleRoot {
+        merkle_root: hex::encode(airdrop_leaf(&addr2, 50)),
+        total_amount: Uint128::new(50),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator, msg).unwrap();
+    let claim = Execute::ClaimAirdrop {
+        stage: 1,
+        amount: Uint128::new(50),
+        proof: vec![],
+    };
+    let claimant = mock_info(addr2.as_ref(), &[]);
+    let err = execute(deps.as_mut(), mock_env(), claimant.clone(), claim.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PolicyDenied {
+            reason: String::from("sanctioned")
+        }
+    );
+    deps.querier = PolicyQuerier(Some(PolicyResponse {
+        allowed: true,
+        reason: None,
+    }));
+    let _ = execute(deps.as_mut(), mock_env(), info, transfer).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(200));
+    let _ = execute(deps.as_mut(), mock_env(), claimant, claim).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), &addr2), Uint128::new(250));
+}
+
+#[test]
+fn test_update_minter() {
+    let mut deps = mock_dependencies(&[]);
+    let minter = String::from("minter");
+    let multisig = String::from("multisig");
+    do_instantiate_with_minter(
+        deps.as_mut(),
+        String::from("addr0001"),
+        Uint128::new(1000),
+        String::from("addr0002"),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+        minter.clone(),
+        Some(Uint128::new(10000)),
+    );
+
+    // only the minter or an admin can hand over the authority
+    let msg = Execute::UpdateMinter {
+        new_minter: Some(multisig.clone()),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info(&minter, &[]), msg).unwrap();
+    assert_eq!(res.events[0].ty, "update_minter");
+    assert_eq!(res.events[0].attributes[0].value, minter);
+    assert_eq!(res.events[0].attributes[1].value, multisig);
+    let token_info = TOKEN_INFO.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        token_info.mint,
+        Some(MinterData {
+            minter: Addr::unchecked(&multisig),
+            cap: Some(Uint128::new(10000)),
+        })
+    );
+
+    // the previous minter lost the authority
+    let msg = Execute::Mint {
+        recipient: String::from("addr0002"),
+        amount: Uint128::new(100),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&minter, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let _ = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&multisig, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+
+    // revoking is permanent
+    let revoke = Execute::UpdateMinter { new_minter: None };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), revoke).unwrap();
+    assert_eq!(TOKEN_INFO.load(deps.as_ref().storage).unwrap().mint, None);
+    let err = execute(deps.as_mut(), mock_env(), mock_info(&multisig, &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // the supply cap outlives the minter
+    let _ = sudo(
+        deps.as_mut(),
+        mock_env(),
+        SudoMsg::UpdateBalanceCap {
+            new_cap: Uint128::new(20000),
+        },
+    )
+    .unwrap();
+    let msg = Execute::AddMinter {
+        address: multisig.clone(),
+        cap: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+    let mint = |amount: u128| Execute::Mint {
+        recipient: String::from("addr0002"),
+        amount: Uint128::new(amount),
+    };
+    let info = mock_info(&multisig, &[]);
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), mint(8901)).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+    let _ = execute(deps.as_mut(), mock_env(), info, mint(8900)).unwrap();
+}
+
+#[test]
+fn test_minters() {
+    let mut deps = mock_dependencies(&[]);
+    let treasury = String::from("treasury");
+    let rcpt = String::from("addr0002");
+    do_instantiate_with_minter(
+        deps.as_mut(),
+        String::from("addr0001"),
+        Uint128::new(1000),
+        rcpt.clone(),
+        Uint128::zero(),
+        Uint128::zero(),
+        Uint128::new(5000),
+        String::from("minter"),
+        Some(Uint128::new(2000)),
+    );
+    let mint = |amount: u128| Execute::Mint {
+        recipient: rcpt.clone(),
+        amount: Uint128::new(amount),
+    };
+
+    let msg = Execute::AddMinter {
+        address: treasury.clone(),
+        cap: Some(Uint128::new(500)),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(&treasury, &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let creator = mock_info("creator", &[]);
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+
+    // registered minters mint within their own cumulative cap
+    let info = mock_info(&treasury, &[]);
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), mint(300)).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), mint(300)).unwrap_err();
+    assert_eq!(err, ContractError::MinterCapExceeded {});
+    assert_eq!(get_balance(deps.as_ref(), &rcpt), Uint128::new(300));
+    assert_eq!(
+        query_minters(deps.as_ref(), None, None).unwrap().minters,
+        vec![MinterInfo {
+            minter: treasury.clone(),
+            cap: Some(Uint128::new(500)),
+            minted: Uint128::new(300),
+        }]
+    );
+
+    // the global mint cap still applies
+    let msg = Execute::SetMinterCap {
+        address: treasury.clone(),
+        cap: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), mint(800)).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+    let _ = execute(deps.as_mut(), mock_env(), info.clone(), mint(700)).unwrap();
+
+    // the original minter keeps minting through cw20-base
+    let err = execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), mint(1)).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+
+    // revoking the cw20 minter leaves the supply cap in place for registered minters
+    let msg = Execute::UpdateMinter { new_minter: None };
+    let _ = execute(deps.as_mut(), mock_env(), creator.clone(), msg).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info.clone(), mint(1)).unwrap_err();
+    assert_eq!(err, ContractError::CannotExceedCap {});
+    assert_eq!(get_balance(deps.as_ref(), &rcpt), Uint128::new(1000));
+    // nobody can mint past the cap, so minting is no longer advertised
+    let res = query_capabilities(deps.as_ref()).unwrap();
+    assert!(!res.extensions.contains(&String::from("mintable")));
+
+    let msg = Execute::RemoveMinter {
+        address: treasury.clone(),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), creator, msg).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), info, mint(1)).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn test_instantiate_marketing() {
+    let mut deps = mock_dependencies(&[]);
+    let png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00];
+    let instantiate_msg = |logo: Logo| Instantiate {
+        name: "Bash Shell".to_string(),
+        symbol: "BASH".to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: None,
+        frozen_balances: vec![],
+        bal_cap: Uint128::from(1000u128),
+        vesting: vec![],
+        marketing: Some(InstantiateMarketingInfo {
+            project: Some("Bash".to_string()),
+            description: Some("Shell token".to_string()),
+            marketing: Some("marketing".to_string()),
+            logo: Some(logo),
+        }),
+        initial_whitelist: vec![],
+        initial_burnlist: vec![],
+    };
+
+    // malformed embedded logos are rejected
+    let info = mock_info("creator", &[]);
+    let msg = instantiate_msg(Logo::Embedded(EmbeddedLogo::Svg(b"<svg></svg>".into())));
+    let err = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidXmlPreamble {});
+
+    let info = mock_info("creator", &[]);
+    let msg = instantiate_msg(Logo::Embedded(EmbeddedLogo::Png(png.clone().into())));
+    let _ = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    assert_eq!(
+        query_marketing_info(deps.as_ref()).unwrap(),
+        MarketingInfoResponse {
+            project: Some("Bash".to_string()),
+            description: Some("Shell token".to_string()),
+            marketing: Some(Addr::unchecked("marketing")),
+            logo: Some(LogoInfo::Embedded),
+        }
+    );
+    let logo = query_download_logo(deps.as_ref()).unwrap();
+    assert_eq!(logo.mime_type, "image/png");
+    assert_eq!(logo.data, Binary::from(png));
+
+    // the marketing address set at instantiate can update the info afterwards
+    let info = mock_info("marketing", &[]);
+    let msg = Execute::UpdateMarketing {
+        project: None,
+        description: Some("".to_string()),
+        marketing: None,
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        query_marketing_info(deps.as_ref()).unwrap().description,
+        None
+    );
+}
+
+#[test]
+fn test_instantiate_lists() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let sink = String::from("sink");
+    let mut instantiate_msg = Instantiate {
+        name: "Bash Shell".to_string(),
+        symbol: "BASH".to_string(),
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: addr1.clone(),
+            amount: Uint128::new(1000),
+        }],
+        mint: None,
+        frozen_balances: vec![],
+        bal_cap: Uint128::new(1000),
+        vesting: vec![],
+        marketing: None,
+        initial_whitelist: vec![addr1.clone(), "".to_string()],
+        initial_burnlist: vec![sink.clone()],
+    };
+
+    // every listed address is validated
+    let info = mock_info("creator", &[]);
+    let err = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg.clone()).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Std(StdError::GenericErr { .. })
+    ));
+
+    instantiate_msg.initial_whitelist = vec![addr1.clone()];
+    let info = mock_info("creator", &[]);
+    let _ = instantiate(deps.as_mut(), mock_env(), info, instantiate_msg).unwrap();
+    assert!(
+        query_is_whitelisted(deps.as_ref(), addr1.clone())
+            .unwrap()
+            .whitelisted
+    );
+
+    // a listed burn address is not held to the balance cap
+    let info = mock_info(addr1.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: sink.clone(),
+        amount: Uint128::new(1000),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(get_balance(deps.as_ref(), sink), Uint128::new(1000));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    /// Each step is (action, owner, other account, amount), so a failing sequence shrinks
+    /// towards fewer steps, the first accounts and smaller amounts
+    #[test]
+    fn test_random_sequences_keep_invariants(
+        steps in vec((0..5u8, 0..4usize, 0..4usize, 0..1500u128), 1..200)
+    ) {
+        let accounts = ["addr0001", "addr0002", "addr0003", "addr0004"];
+        let bal_cap = Uint128::new(3000);
+        let mut deps = mock_dependencies(&[]);
+        do_instantiate_with_minter(
+            deps.as_mut(),
+            accounts[0].to_string(),
+            Uint128::new(2000),
+            accounts[1].to_string(),
+            Uint128::new(1000),
+            Uint128::new(500),
+            bal_cap,
+            String::from("minter"),
+            Some(Uint128::new(20000)),
+        );
+
+        for (action, owner, other, amount) in steps {
+            let owner = accounts[owner];
+            let other = accounts[other].to_string();
+            let amount = Uint128::new(amount);
+            let balance = get_balance(deps.as_ref(), owner);
+            let frozen = get_frozen_balance(deps.as_ref(), owner);
+            let (sender, msg) = match action {
+                0 => (
+                    owner,
+                    Execute::Transfer {
+                        recipient: other,
+                        amount,
+                    },
+                ),
+                1 => (
+                    "minter",
+                    Execute::Mint {
+                        recipient: other,
+                        amount,
+                    },
+                ),
+                2 => (owner, Execute::Burn { amount }),
+                // freezing never goes beyond what is held, as compliance would check
+                3 => (
+                    "creator",
+                    Execute::UpdateFrozenList(UpdateType::Add(Cw20Coin {
+                        address: owner.to_string(),
+                        amount: amount.min(balance.saturating_sub(frozen)),
+                    })),
+                ),
+                _ => (
+                    "creator",
+                    Execute::UpdateFrozenList(UpdateType::Sub(Cw20Coin {
+                        address: owner.to_string(),
+                        amount,
+                    })),
+                ),
+            };
+
+            // a failed transaction leaves no trace, as on chain
+            let snapshot: Vec<_> = deps.storage.range(None, None, Order::Ascending).collect();
+            let info = mock_info(sender, &[]);
+            if execute(deps.as_mut(), mock_env(), info, msg).is_err() {
+                deps.storage = MockStorage::new();
+                for (key, value) in snapshot {
+                    deps.storage.set(&key, &value);
+                }
+            }
+
+            let total_supply = query_token_info(deps.as_ref()).unwrap().total_supply;
+            let mut held = Uint128::zero();
+            let mut frozen_total = Uint128::zero();
+            for account in accounts {
+                let balance = get_balance(deps.as_ref(), account);
+                let frozen = get_frozen_balance(deps.as_ref(), account);
+                prop_assert!(balance <= bal_cap, "{} over cap", account);
+                prop_assert!(frozen <= balance, "{} over frozen", account);
+                let indexed = holders()
+                    .may_load(deps.as_ref().storage, &Addr::unchecked(account))
+                    .unwrap();
+                prop_assert_eq!(indexed.unwrap_or_default(), balance);
+                held += balance;
+                frozen_total += frozen;
+            }
+            prop_assert_eq!(held, total_supply, "supply not conserved");
+
+            let breakdown = query_supply_breakdown(deps.as_ref(), mock_env()).unwrap();
+            prop_assert_eq!(breakdown.frozen, frozen_total);
+            prop_assert_eq!(
+                breakdown.total_minted - breakdown.total_burned,
+                total_supply
+            );
+        }
+    }
+}
+
+#[test]
+fn test_top_holders() {
+    let mut deps = mock_dependencies(&[]);
+    let addr1 = String::from("addr0001");
+    let addr2 = String::from("addr0002");
+    let addr3 = String::from("addr0003");
+    do_instantiate(
+        deps.as_mut(),
+        addr1.clone(),
+        Uint128::new(2000),
+        addr2.clone(),
+        Uint128::new(2600),
+        Uint128::zero(),
+        Uint128::new(5000),
+    );
+    let coin = |address: &String, amount: u128| Cw20Coin {
+        address: address.clone(),
+        amount: Uint128::new(amount),
+    };
+
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Transfer {
+        recipient: addr3.clone(),
+        amount: Uint128::new(2000),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        query_top_holders(deps.as_ref(), None, None)
+            .unwrap()
+            .holders,
+        vec![coin(&addr3, 2000), coin(&addr1, 2000), coin(&addr2, 600)]
+    );
+
+    // pages continue after the last holder returned
+    let page = query_top_holders(deps.as_ref(), None, Some(1)).unwrap();
+    assert_eq!(page.holders, vec![coin(&addr3, 2000)]);
+    let page = query_top_holders(deps.as_ref(), Some(addr3.clone()), Some(2)).unwrap();
+    assert_eq!(page.holders, vec![coin(&addr1, 2000), coin(&addr2, 600)]);
+
+    // emptied accounts drop out of the list
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::Burn {
+        amount: Uint128::new(600),
+    };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        query_top_holders(deps.as_ref(), None, None)
+            .unwrap()
+            .holders,
+        vec![coin(&addr3, 2000), coin(&addr1, 2000)]
+    );
+
+    // a 1.4.0 deployment gets the index built from its balances by the backfill
+    for address in [&addr1, &addr3] {
+        holders()
+            .remove(deps.as_mut().storage, &Addr::unchecked(address))
+            .unwrap();
+    }
+    assert!(query_top_holders(deps.as_ref(), None, None)
+        .unwrap()
+        .holders
+        .is_empty());
+    cw2::set_contract_version(deps.as_mut().storage, "token_contract", "1.4.0").unwrap();
+    let _ = migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).unwrap();
+    let res = query_top_holders(deps.as_ref(), None, None).unwrap();
+    assert!(res.holders.is_empty());
+    assert!(!res.complete);
+
+    let info = mock_info(addr2.as_ref(), &[]);
+    let msg = Execute::MigrateHolders { limit: None };
+    let _ = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        query_top_holders(deps.as_ref(), None, None).unwrap(),
+        TopHoldersResponse {
+            holders: vec![coin(&addr3, 2000), coin(&addr1, 2000)],
+            complete: true,
+        }
+    );
+}